@@ -0,0 +1,111 @@
+//! Scanning a synthetic home directory with a few hundred skills, with an
+//! empty cache and with everything unchanged since the last scan. Half the
+//! skills have provenance recorded at another path, so they are recognised
+//! by content hash.
+//!
+//! Run with `cargo bench --no-default-features --bench scan`.
+
+use std::fs;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tauri_appskills_manager_lib::{digest, scanner, skills};
+
+const AGENT_DIRS: [&str; 4] = [
+    ".claude/skills",
+    ".cursor/skills",
+    ".codex/skills",
+    ".agents/skills",
+];
+const SKILLS_PER_AGENT: usize = 100;
+/// Every nth skill is a git checkout
+const GIT_EVERY: usize = 10;
+/// Every nth skill has provenance recorded before it was moved
+const MOVED_EVERY: usize = 2;
+
+fn write_skill(dir: &Path, name: &str, git: bool) {
+    fs::create_dir_all(dir.join("scripts")).unwrap();
+    fs::write(
+        dir.join("SKILL.md"),
+        format!(
+            "---\nname: {name}\ndescription: Synthetic skill {name}\nversion: 1.0.0\ntags: [bench]\n---\n\n# {name}\n"
+        ),
+    )
+    .unwrap();
+    fs::write(dir.join("scripts").join("run.sh"), "#!/bin/sh\necho ok\n").unwrap();
+    if git {
+        let repo = git2::Repository::init(dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("bench", "bench@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+    }
+}
+
+/// Points the home, config and data directories at a new tree of skills.
+fn synthetic_home() -> tempfile::TempDir {
+    let home = tempfile::tempdir().unwrap();
+    let mut entries = Vec::new();
+    for agent_dir in AGENT_DIRS {
+        for i in 0..SKILLS_PER_AGENT {
+            let name = format!("skill-{i:03}");
+            let dir = home.path().join(agent_dir).join(&name);
+            write_skill(&dir, &name, i % GIT_EVERY == 0);
+            if i % MOVED_EVERY == 0 {
+                entries.push(serde_json::json!({
+                    "name": name,
+                    "agent": "claude",
+                    "path": home.path().join("moved").join(&name),
+                    "content_hash": digest::dir_digest(&dir).unwrap(),
+                    "source": format!("bench/{name}"),
+                }));
+            }
+        }
+    }
+    let data = home.path().join("data");
+    fs::create_dir_all(&data).unwrap();
+    fs::write(
+        data.join("skill_sources.json"),
+        serde_json::json!({ "version": 2, "entries": entries }).to_string(),
+    )
+    .unwrap();
+    std::env::set_var("HOME", home.path());
+    std::env::set_var("XDG_CONFIG_HOME", home.path().join(".config"));
+    std::env::set_var("XDG_DATA_HOME", home.path().join(".local/share"));
+    std::env::set_var("SKILLS_MANAGER_DATA_DIR", data);
+    home
+}
+
+fn bench_scan(c: &mut Criterion) {
+    let _home = synthetic_home();
+    let expected = AGENT_DIRS.len() * SKILLS_PER_AGENT;
+    let found = skills::local_skills().unwrap();
+    assert_eq!(found.len(), expected);
+    let recognised = found.iter().filter(|s| s.source.is_some()).count();
+    assert!(recognised >= expected / MOVED_EVERY);
+
+    // An unchanged tree is served from the cache, digests included
+    let before = scanner::stats();
+    skills::local_skills().unwrap();
+    assert_eq!(scanner::stats(), before);
+
+    let mut group = c.benchmark_group(format!("local_skills/{expected}"));
+    group.bench_function("cold", |b| {
+        b.iter(|| {
+            scanner::clear();
+            skills::local_skills().unwrap()
+        })
+    });
+    group.bench_function("unchanged", |b| {
+        skills::local_skills().unwrap();
+        b.iter(skills::local_skills)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_scan);
+criterion_main!(benches);
@@ -0,0 +1,296 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, SkillsError};
+use crate::paths;
+
+/// Id of the shared `~/.agents/skills` store that other agents link into.
+pub const GLOBAL_AGENT: &str = "global";
+
+const AGENTS_FILE: &str = "agents.json";
+
+// Built-in Agent Id -> Display Name -> Path relative to home -> Path relative to a project root
+#[rustfmt::skip]
+const BUILTIN_AGENTS: &[(&str, &str, &str, &str)] = &[
+    (GLOBAL_AGENT, "Global", ".agents/skills", ".agents/skills"), // Official global skills path
+    ("antigravity", "Antigravity", ".gemini/antigravity/skills", ".agent/skills"),
+    ("claude-code", "Claude Code", ".claude/skills", ".claude/skills"),
+    ("cursor", "Cursor", ".cursor/skills", ".cursor/skills"),
+    ("windsurf", "Windsurf", ".codeium/windsurf/skills", ".windsurf/skills"),
+    ("trae", "Trae", ".trae/skills", ".trae/skills"),
+    ("trae-cn", "Trae CN", ".trae-cn/skills", ".trae-cn/skills"),
+    ("roo", "Roo Code", ".roo/skills", ".roo/skills"),
+    ("cline", "Cline", ".cline/skills", ".cline/skills"),
+    ("gemini-cli", "Gemini CLI", ".gemini/skills", ".gemini/skills"),
+    ("github-copilot", "GitHub Copilot", ".copilot/skills", ".github/skills"),
+    ("amp", "Amp", ".config/agents/skills", ".agents/skills"),
+    ("kimi-cli", "Kimi Code CLI", ".config/agents/skills", ".agents/skills"),
+    ("augment", "Augment", ".augment/rules", ".augment/rules"),
+    ("openclaw", "OpenClaw", ".openclaw/skills", ".openclaw/skills"),
+    ("codebuddy", "CodeBuddy", ".codebuddy/skills", ".codebuddy/skills"),
+    ("codex", "Codex", ".codex/skills", ".agents/skills"),
+    ("command-code", "Command Code", ".commandcode/skills", ".commandcode/skills"),
+    ("continue", "Continue", ".continue/skills", ".continue/skills"),
+    ("crush", "Crush", ".config/crush/skills", ".crush/skills"),
+    ("droid", "Droid", ".factory/skills", ".factory/skills"),
+    ("goose", "Goose", ".config/goose/skills", ".goose/skills"),
+    ("junie", "Junie", ".junie/skills", ".junie/skills"),
+    ("iflow-cli", "iFlow CLI", ".iflow/skills", ".iflow/skills"),
+    ("kilo", "Kilo Code", ".kilocode/skills", ".kilocode/skills"),
+    ("kiro-cli", "Kiro CLI", ".kiro/skills", ".kiro/skills"),
+    ("kode", "Kode", ".kode/skills", ".kode/skills"),
+    ("mcpjam", "MCPJam", ".mcpjam/skills", ".mcpjam/skills"),
+    ("mistral-vibe", "Mistral Vibe", ".vibe/skills", ".vibe/skills"),
+    ("mux", "Mux", ".mux/skills", ".mux/skills"),
+    ("opencode", "OpenCode", ".config/opencode/skills", ".agents/skills"),
+    ("openclaude", "OpenClaude IDE", ".openclaude/skills", ".openclaude/skills"),
+    ("openhands", "OpenHands", ".openhands/skills", ".openhands/skills"),
+    ("pi", "Pi", ".pi/agent/skills", ".pi/skills"),
+    ("qoder", "Qoder", ".qoder/skills", ".qoder/skills"),
+    ("qwen-code", "Qwen Code", ".qwen/skills", ".qwen/skills"),
+    ("replit", "Replit", ".replit/skills", ".agents/skills"),
+    ("zencoder", "Zencoder", ".zencoder/skills", ".zencoder/skills"),
+    ("neovate", "Neovate", ".neovate/skills", ".neovate/skills"),
+    ("pochi", "Pochi", ".pochi/skills", ".pochi/skills"),
+    ("adal", "AdaL", ".adal/skills", ".adal/skills"),
+];
+
+/// A fully resolved agent: built-in defaults with user overrides applied.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AgentDefinition {
+    pub id: String,
+    pub name: String,
+    /// Skills directory, relative to the home directory unless absolute
+    pub path: String,
+    /// Skills directory inside a project, relative to the project root
+    pub project_path: String,
+    pub enabled: bool,
+    pub builtin: bool,
+}
+
+impl AgentDefinition {
+    pub fn skills_dir(&self, home: &Path) -> PathBuf {
+        home.join(&self.path)
+    }
+
+    pub fn project_skills_dir(&self, project_root: &Path) -> PathBuf {
+        project_root.join(&self.project_path)
+    }
+}
+
+/// One entry of the user's agents.json. Missing fields keep the built-in value.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AgentOverride {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct AgentOverrides {
+    #[serde(default)]
+    agents: Vec<AgentOverride>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgentRegistry {
+    agents: Vec<AgentDefinition>,
+}
+
+impl AgentRegistry {
+    /// Loads the built-in agents merged with the user's agents.json.
+    pub fn load() -> Result<Self> {
+        let overrides = read_overrides()?;
+        Ok(Self::with_overrides(&overrides.agents))
+    }
+
+    pub(crate) fn with_overrides(overrides: &[AgentOverride]) -> Self {
+        let mut agents: Vec<AgentDefinition> = BUILTIN_AGENTS
+            .iter()
+            .map(|(id, name, path, project_path)| AgentDefinition {
+                id: id.to_string(),
+                name: name.to_string(),
+                path: path.to_string(),
+                project_path: project_path.to_string(),
+                enabled: true,
+                builtin: true,
+            })
+            .collect();
+
+        for entry in overrides {
+            match agents.iter_mut().find(|a| a.id == entry.id) {
+                Some(agent) => {
+                    if let Some(name) = &entry.name {
+                        agent.name = name.clone();
+                    }
+                    if let Some(path) = &entry.path {
+                        agent.path = path.clone();
+                    }
+                    if let Some(project_path) = &entry.project_path {
+                        agent.project_path = project_path.clone();
+                    }
+                    if let Some(enabled) = entry.enabled {
+                        agent.enabled = enabled;
+                    }
+                }
+                // Custom agents without a path can't be scanned, skip them
+                None => {
+                    if let Some(path) = &entry.path {
+                        agents.push(AgentDefinition {
+                            id: entry.id.clone(),
+                            name: entry.name.clone().unwrap_or_else(|| entry.id.clone()),
+                            path: path.clone(),
+                            // Default to the same layout as in the home directory
+                            project_path: entry
+                                .project_path
+                                .clone()
+                                .unwrap_or_else(|| path.clone()),
+                            enabled: entry.enabled.unwrap_or(true),
+                            builtin: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        AgentRegistry { agents }
+    }
+
+    /// Every known agent, including disabled ones.
+    pub fn all(&self) -> &[AgentDefinition] {
+        &self.agents
+    }
+
+    pub fn enabled(&self) -> impl Iterator<Item = &AgentDefinition> {
+        self.agents.iter().filter(|a| a.enabled)
+    }
+
+    /// Looks up an enabled agent by id.
+    pub fn get(&self, id: &str) -> Option<&AgentDefinition> {
+        self.enabled().find(|a| a.id == id)
+    }
+
+    pub fn require(&self, id: &str) -> Result<&AgentDefinition> {
+        self.get(id).ok_or_else(|| SkillsError::UnknownAgent {
+            agent: id.to_string(),
+        })
+    }
+}
+
+fn agents_file_path() -> Result<PathBuf> {
+    paths::config_dir()
+        .map(|dir| dir.join(AGENTS_FILE))
+        .ok_or_else(|| SkillsError::Other("Could not find config directory".to_string()))
+}
+
+fn read_overrides() -> Result<AgentOverrides> {
+    let path = agents_file_path()?;
+    if !path.exists() {
+        return Ok(AgentOverrides::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+    serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))
+}
+
+fn write_overrides(overrides: &AgentOverrides) -> Result<()> {
+    let path = agents_file_path()?;
+    paths::ensure_parent(&path)?;
+    let content =
+        serde_json::to_string_pretty(overrides).map_err(|e| SkillsError::Other(e.to_string()))?;
+    fs::write(&path, content).map_err(|e| SkillsError::io(&path, e))
+}
+
+/// Adds a custom agent or changes a built-in one, returning the resolved agent.
+pub fn upsert_agent(entry: AgentOverride) -> Result<AgentDefinition> {
+    let id = entry.id.trim().to_string();
+    if id.is_empty() {
+        return Err(SkillsError::InvalidInput(
+            "Agent id must not be empty".to_string(),
+        ));
+    }
+
+    let mut overrides = read_overrides()?;
+    let is_builtin = BUILTIN_AGENTS
+        .iter()
+        .any(|(builtin, _, _, _)| *builtin == id);
+    let existing = overrides.agents.iter_mut().find(|a| a.id == id);
+
+    match existing {
+        Some(current) => {
+            if entry.name.is_some() {
+                current.name = entry.name;
+            }
+            if entry.path.is_some() {
+                current.path = entry.path;
+            }
+            if entry.project_path.is_some() {
+                current.project_path = entry.project_path;
+            }
+            if entry.enabled.is_some() {
+                current.enabled = entry.enabled;
+            }
+        }
+        None => {
+            if !is_builtin && entry.path.is_none() {
+                return Err(SkillsError::InvalidInput(format!(
+                    "Custom agent '{}' needs a skills path",
+                    id
+                )));
+            }
+            overrides.agents.push(AgentOverride {
+                id: id.clone(),
+                ..entry
+            });
+        }
+    }
+
+    write_overrides(&overrides)?;
+    AgentRegistry::with_overrides(&overrides.agents)
+        .all()
+        .iter()
+        .find(|a| a.id == id)
+        .cloned()
+        .ok_or(SkillsError::UnknownAgent { agent: id })
+}
+
+/// Deletes a custom agent. Built-in agents can't be deleted, so they're disabled instead.
+pub fn remove_agent(id: &str) -> Result<()> {
+    let mut overrides = read_overrides()?;
+    let is_builtin = BUILTIN_AGENTS
+        .iter()
+        .any(|(builtin, _, _, _)| *builtin == id);
+
+    if is_builtin {
+        if id == GLOBAL_AGENT {
+            return Err(SkillsError::InvalidInput(
+                "The global agent cannot be removed".to_string(),
+            ));
+        }
+        match overrides.agents.iter_mut().find(|a| a.id == id) {
+            Some(entry) => entry.enabled = Some(false),
+            None => overrides.agents.push(AgentOverride {
+                id: id.to_string(),
+                name: None,
+                path: None,
+                project_path: None,
+                enabled: Some(false),
+            }),
+        }
+    } else {
+        let before = overrides.agents.len();
+        overrides.agents.retain(|a| a.id != id);
+        if overrides.agents.len() == before {
+            return Err(SkillsError::UnknownAgent {
+                agent: id.to_string(),
+            });
+        }
+    }
+
+    write_overrides(&overrides)
+}
@@ -0,0 +1,999 @@
+//! Headless front end for machines without a display.
+//!
+//! Prints tables by default or JSON with `--json`. Exit codes:
+//! 0 ok, 1 other error, 2 bad arguments, 3 skill/agent/path not found,
+//! 4 permission denied, 5 npx missing, 6 network, git or command failure,
+//! 7 cancelled or timed out, 8 some items of a batch or sync failed,
+//! 9 content doesn't match skills.lock or was changed since install.
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use tauri_appskills_manager_lib::doctor::{self, CheckStatus};
+use tauri_appskills_manager_lib::duplicates::{self, DuplicateStatus};
+use tauri_appskills_manager_lib::error::{Result, SkillsError};
+use tauri_appskills_manager_lib::installer::{Backend, InstallMode, InstallRequest, RemoveRequest};
+use tauri_appskills_manager_lib::links::{self, LinkFix, LinkProblem};
+use tauri_appskills_manager_lib::operations::{NullSink, Operation};
+use tauri_appskills_manager_lib::scope::{self, SkillScope};
+use tauri_appskills_manager_lib::settings;
+use tauri_appskills_manager_lib::skills::{self, SkillsExport};
+use tauri_appskills_manager_lib::team::{self, SyncPlan};
+use tauri_appskills_manager_lib::trash::{self, TrashEntry};
+use tauri_appskills_manager_lib::vault::{self, Vault};
+use tauri_appskills_manager_lib::verify::{self, IntegrityStatus};
+use tauri_appskills_manager_lib::watcher::{ChangeSink, SkillsChanged, SkillsWatcher};
+
+const EXIT_ERROR: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_NOT_FOUND: u8 = 3;
+const EXIT_PERMISSION: u8 = 4;
+const EXIT_MISSING_TOOL: u8 = 5;
+const EXIT_REMOTE: u8 = 6;
+const EXIT_INTERRUPTED: u8 = 7;
+const EXIT_PARTIAL: u8 = 8;
+const EXIT_INTEGRITY: u8 = 9;
+
+#[derive(Parser)]
+#[command(
+    name = "skills-manager",
+    version,
+    about = "Manage agent skills from the command line"
+)]
+struct Cli {
+    /// Print JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// List installed skills
+    List {
+        /// Only skills installed for this agent
+        #[arg(long)]
+        agent: Option<String>,
+        /// List the shared global store instead
+        #[arg(long)]
+        global: bool,
+    },
+    /// Install skills from a repository, URL or local path
+    Install {
+        source: String,
+        /// Branch, tag or commit to install
+        #[arg(long = "ref")]
+        reference: Option<String>,
+        /// Install only this skill from the source
+        #[arg(long)]
+        skill: Option<String>,
+        /// Agents to install for (repeatable)
+        #[arg(long = "agent", short)]
+        agents: Vec<String>,
+        /// Also install into the shared global store
+        #[arg(long)]
+        global: bool,
+        #[arg(long, default_value = "symlink", value_parser = ["symlink", "copy"])]
+        mode: String,
+        /// Install into this project instead of the home directory
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long, value_parser = ["native", "npx"])]
+        backend: Option<String>,
+        /// Install the commit in skills.lock and fail if the content differs
+        #[arg(long)]
+        frozen: bool,
+    },
+    /// Remove skills
+    Remove {
+        skill_ids: Vec<String>,
+        #[arg(long = "agent", short)]
+        agents: Vec<String>,
+        #[arg(long)]
+        global: bool,
+        /// Remove every skill for the given agents
+        #[arg(long)]
+        all: bool,
+        /// Also remove links from other agents to a removed copy
+        #[arg(long)]
+        cascade: bool,
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long, value_parser = ["native", "npx"])]
+        backend: Option<String>,
+    },
+    /// Switch an installed skill between a link into the global store and a copy
+    Convert {
+        skill: String,
+        #[arg(long)]
+        agent: String,
+        #[arg(long, value_parser = ["symlink", "copy"])]
+        mode: String,
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Pull a git-backed skill, or with `--check` list skills behind their remote
+    Update {
+        #[arg(required_unless_present = "check")]
+        id: Option<String>,
+        #[arg(long, required_unless_present = "check")]
+        agent: Option<String>,
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long)]
+        check: bool,
+    },
+    /// Report skills whose files changed since they were installed
+    Verify {
+        /// Also list skills that are intact or can't be checked
+        #[arg(long)]
+        all: bool,
+    },
+    /// Find skills installed as separate copies for several agents, and
+    /// which copies have drifted apart
+    Duplicates {
+        /// Only list skills whose copies differ
+        #[arg(long)]
+        drifted: bool,
+    },
+    /// Check that git, config files and the data directory are usable, and
+    /// find skill links that are dangling, loop or point outside the store
+    Doctor {
+        /// Repair broken links: point them at the global copy, replace them
+        /// with a real copy, or move them to the trash
+        #[arg(long, value_parser = ["relink", "copy", "delete"])]
+        fix: Option<String>,
+        /// With --fix, only repair these links
+        #[arg(requires = "fix")]
+        links: Vec<String>,
+    },
+    /// Write installed skills in the format the Settings page imports
+    Export {
+        /// Write here instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
+        /// Write a team manifest (skills.toml) instead
+        #[arg(long)]
+        manifest: bool,
+        /// With --manifest, describe this project instead of the home directory
+        #[arg(long, requires = "manifest")]
+        project: Option<String>,
+    },
+    /// Install, upgrade and remove skills to match a team manifest (skills.toml)
+    Sync {
+        /// Manifest to read (`-` for stdin)
+        #[arg(default_value = "skills.toml")]
+        file: String,
+        /// Only print what would change
+        #[arg(long)]
+        plan: bool,
+        /// Install what skills.lock records and fail on any difference
+        #[arg(long, conflicts_with = "plan")]
+        frozen: bool,
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Install every skill listed in an export file (`-` for stdin)
+    Import {
+        file: String,
+        /// Agents for entries that don't list any
+        #[arg(long = "agent", short)]
+        agents: Vec<String>,
+        #[arg(long)]
+        global: bool,
+        #[arg(long, default_value = "symlink", value_parser = ["symlink", "copy"])]
+        mode: String,
+        #[arg(long)]
+        project: Option<String>,
+        #[arg(long, value_parser = ["native", "npx"])]
+        backend: Option<String>,
+    },
+    /// Print skills as they change on disk, until interrupted
+    Watch,
+    /// List, restore or purge removed skills
+    Trash {
+        #[command(subcommand)]
+        action: Option<TrashAction>,
+    },
+    /// Read secrets of skill configs from the encrypted vault. The passphrase
+    /// comes from SKILLS_MANAGER_VAULT_PASSPHRASE
+    Vault {
+        #[command(subcommand)]
+        action: Option<VaultAction>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VaultAction {
+    /// Where the vault is and how many secrets it holds (the default)
+    Status,
+    /// Print the secret a `vault:<id>` reference in skill.config.json stands for
+    Get { reference: String },
+}
+
+#[derive(Subcommand)]
+enum TrashAction {
+    /// List trashed skills, most recent first (the default)
+    List,
+    /// Put trashed skills back where they were removed from
+    Restore {
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Delete trashed skills for good
+    Purge {
+        #[arg(required_unless_present = "all")]
+        ids: Vec<String>,
+        /// Empty the whole trash
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command, cli.json) {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            if cli.json {
+                print_json(&ErrorOutput { error: &e });
+            } else {
+                eprintln!("error: {}", e);
+                if let SkillsError::HasDependents { .. } = e {
+                    eprintln!("hint: pass --cascade to remove those links too");
+                }
+            }
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorOutput<'a> {
+    error: &'a SkillsError,
+}
+
+fn exit_code(err: &SkillsError) -> u8 {
+    match err {
+        SkillsError::InvalidInput(_) => EXIT_USAGE,
+        SkillsError::UnknownAgent { .. }
+        | SkillsError::SkillNotFound { .. }
+        | SkillsError::NotFound { .. } => EXIT_NOT_FOUND,
+        SkillsError::PermissionDenied { .. } | SkillsError::VaultLocked { .. } => EXIT_PERMISSION,
+        SkillsError::CommandNotFound { .. } => EXIT_MISSING_TOOL,
+        SkillsError::Network { .. }
+        | SkillsError::CommandFailed { .. }
+        | SkillsError::Git { .. } => EXIT_REMOTE,
+        SkillsError::Cancelled | SkillsError::TimedOut { .. } => EXIT_INTERRUPTED,
+        SkillsError::IntegrityMismatch { .. } => EXIT_INTEGRITY,
+        _ => EXIT_ERROR,
+    }
+}
+
+/// Prints each batch of changes as it arrives: one JSON object per line
+/// with `--json`, otherwise one line per skill.
+struct PrintSink {
+    json: bool,
+}
+
+impl ChangeSink for PrintSink {
+    fn emit(&self, event: &SkillsChanged) {
+        if self.json {
+            match serde_json::to_string(event) {
+                Ok(json) => println!("{}", json),
+                Err(e) => eprintln!("error: {}", e),
+            }
+            return;
+        }
+        for change in &event.skills {
+            let status = if change.skill.is_some() {
+                "changed"
+            } else {
+                "removed"
+            };
+            println!("{:<8} {:<12} {}", status, change.agent, change.path);
+        }
+    }
+}
+
+fn start_operation(kind: &str) -> Operation {
+    Operation::start(kind, None, Arc::new(NullSink)).with_timeout(settings::timeout_for(kind))
+}
+
+/// Runs `f` as a tracked operation so timeouts and cleanup match the app.
+fn with_operation<T>(kind: &str, f: impl FnOnce(&Operation) -> Result<T>) -> Result<T> {
+    let op = start_operation(kind);
+    let result = f(&op);
+    op.finish(&result);
+    result
+}
+
+fn run(command: Commands, json: bool) -> Result<u8> {
+    match command {
+        Commands::List { agent, global } => {
+            if global {
+                let skills = skills::global_skills()?;
+                if json {
+                    print_json(&skills);
+                } else {
+                    print_table(
+                        &["ID", "USED BY", "SOURCE"],
+                        skills.iter().map(|s| {
+                            vec![
+                                s.id.clone(),
+                                s.used_by.join(","),
+                                s.source.clone().unwrap_or_default(),
+                            ]
+                        }),
+                    );
+                }
+                return Ok(0);
+            }
+
+            let mut skills = skills::local_skills()?;
+            if let Some(agent) = &agent {
+                skills.retain(|s| &s.agent == agent);
+            }
+            if json {
+                print_json(&skills);
+            } else {
+                print_table(
+                    &["ID", "AGENT", "SCOPE", "VERSION", "SOURCE"],
+                    skills.iter().map(|s| {
+                        vec![
+                            s.id.clone(),
+                            s.agent.clone(),
+                            match &s.scope {
+                                SkillScope::Global => "global".to_string(),
+                                SkillScope::User => "user".to_string(),
+                                SkillScope::Project { path } => path.clone(),
+                            },
+                            s.version.clone().unwrap_or_default(),
+                            s.source.clone().unwrap_or_default(),
+                        ]
+                    }),
+                );
+            }
+            Ok(0)
+        }
+        Commands::Install {
+            source,
+            reference,
+            skill,
+            agents,
+            global,
+            mode,
+            project,
+            backend,
+            frozen,
+        } => {
+            let request = InstallRequest {
+                source,
+                reference,
+                skill,
+                global,
+                agents,
+                mode: InstallMode::parse(&mode)?,
+                project: project.as_deref().map(scope::project_root).transpose()?,
+                auto_confirm: true,
+                frozen,
+            };
+            let backend = Backend::parse(backend.as_deref())?;
+            let report = with_operation("install", |op| skills::install(&request, backend, op))?;
+            if json {
+                print_json(&report);
+            } else {
+                print_table(
+                    &["SKILL", "AGENT", "PATH"],
+                    report.installed.iter().map(|s| {
+                        vec![
+                            s.name.clone(),
+                            s.agent.clone(),
+                            s.path.clone().unwrap_or_default(),
+                        ]
+                    }),
+                );
+                for skipped in &report.skipped {
+                    eprintln!(
+                        "skipped: {}",
+                        serde_json::to_string(skipped).unwrap_or_default()
+                    );
+                }
+            }
+            Ok(0)
+        }
+        Commands::Remove {
+            skill_ids,
+            agents,
+            global,
+            all,
+            cascade,
+            project,
+            backend,
+        } => {
+            if skill_ids.is_empty() && !all {
+                return Err(SkillsError::InvalidInput(
+                    "Name at least one skill or pass --all".to_string(),
+                ));
+            }
+            let request = RemoveRequest {
+                skill_ids,
+                global,
+                agents,
+                remove_all: all,
+                project: project.as_deref().map(scope::project_root).transpose()?,
+                auto_confirm: true,
+                cascade,
+            };
+            let backend = Backend::parse(backend.as_deref())?;
+            let report =
+                with_operation("remove_skills", |op| skills::remove(&request, backend, op))?;
+            if json {
+                print_json(&report);
+            } else {
+                print_table(
+                    &["REMOVED", "AGENT", "PATH", "LINK TO", "TRASH ID"],
+                    report.removed.iter().map(|s| {
+                        vec![
+                            s.name.clone(),
+                            s.agent.clone(),
+                            s.path.clone().unwrap_or_default(),
+                            s.link_target.clone().unwrap_or_default(),
+                            s.trash_id.clone().unwrap_or_default(),
+                        ]
+                    }),
+                );
+                for missing in &report.missing {
+                    eprintln!("not installed: {} ({})", missing.name, missing.agent);
+                }
+            }
+            Ok(if report.missing.is_empty() {
+                0
+            } else {
+                EXIT_PARTIAL
+            })
+        }
+        Commands::Convert {
+            skill,
+            agent,
+            mode,
+            project,
+        } => {
+            let mode = InstallMode::parse(&mode)?;
+            let project = project.as_deref().map(scope::project_root).transpose()?;
+            let converted = with_operation("convert", |op| {
+                skills::convert_install_mode(&skill, &agent, mode, project.as_deref(), op)
+            })?;
+            if json {
+                print_json(&converted);
+            } else if !converted.changed {
+                println!("{} is already installed that way", converted.path);
+            } else {
+                match &converted.linked_to {
+                    Some(target) => println!("linked {} -> {}", converted.path, target),
+                    None => println!("copied into {}", converted.path),
+                }
+                if let Some(id) = &converted.trash_id {
+                    println!("the previous copy is in the trash as {}", id);
+                }
+            }
+            Ok(0)
+        }
+        Commands::Update {
+            id,
+            agent,
+            project,
+            check: true,
+        } => {
+            // Only the project's skills, registered as a workspace or not
+            let mut installed = match project.as_deref().map(scope::project_root).transpose()? {
+                Some(root) => skills::skills_in(Some(&root))?,
+                None => skills::local_skills()?,
+            };
+            installed.retain(|s| {
+                id.as_ref().is_none_or(|id| &s.id == id)
+                    && agent.as_ref().is_none_or(|agent| &s.agent == agent)
+            });
+            let updates =
+                with_operation("check_updates", |op| skills::check_updates(installed, op))?;
+            if json {
+                print_json(&updates);
+            } else {
+                print_table(
+                    &["ID", "REMOTE"],
+                    updates
+                        .iter()
+                        .map(|u| vec![u.id.clone(), u.remote_hash.clone()]),
+                );
+            }
+            Ok(0)
+        }
+        Commands::Update {
+            id, agent, project, ..
+        } => {
+            // clap requires both unless --check was given
+            let (id, agent) = (id.unwrap_or_default(), agent.unwrap_or_default());
+            let output = with_operation("update", |op| {
+                skills::update_skill(&id, &agent, project.as_deref(), op)
+            })?;
+            if json {
+                print_json(&serde_json::json!({ "id": id, "agent": agent, "output": output }));
+            } else {
+                println!("{}", output);
+            }
+            Ok(0)
+        }
+        Commands::Verify { all } => {
+            let mut results = verify::verify_skills()?;
+            let tampered = results
+                .iter()
+                .any(|r| r.status == IntegrityStatus::Modified);
+            if !all {
+                results.retain(|r| r.status == IntegrityStatus::Modified);
+            }
+            if json {
+                print_json(&results);
+            } else {
+                print_table(
+                    &["ID", "AGENT", "STATUS", "CHANGES"],
+                    results.iter().map(|r| {
+                        let status = match r.status {
+                            IntegrityStatus::Intact => "intact",
+                            IntegrityStatus::Modified => "modified",
+                            IntegrityStatus::Unknown => "unknown",
+                        };
+                        let changes = r
+                            .modified
+                            .iter()
+                            .map(|f| format!("~{}", f))
+                            .chain(r.added.iter().map(|f| format!("+{}", f)))
+                            .chain(r.deleted.iter().map(|f| format!("-{}", f)))
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        vec![r.id.clone(), r.agent.clone(), status.to_string(), changes]
+                    }),
+                );
+            }
+            Ok(if tampered { EXIT_INTEGRITY } else { 0 })
+        }
+        Commands::Duplicates { drifted } => {
+            let mut groups = duplicates::find_duplicates()?;
+            if drifted {
+                groups.retain(|g| g.status == DuplicateStatus::Drifted);
+            }
+            if json {
+                print_json(&groups);
+            } else {
+                print_table(
+                    &["NAME", "STATUS", "AGENT", "PATH", "CHANGES"],
+                    groups.iter().flat_map(|g| {
+                        let status = match g.status {
+                            DuplicateStatus::Identical => "identical",
+                            DuplicateStatus::Drifted => "drifted",
+                        };
+                        g.copies.iter().map(move |c| {
+                            let changes = if c.reference {
+                                "(reference)".to_string()
+                            } else {
+                                c.modified
+                                    .iter()
+                                    .map(|f| format!("~{}", f))
+                                    .chain(c.added.iter().map(|f| format!("+{}", f)))
+                                    .chain(c.deleted.iter().map(|f| format!("-{}", f)))
+                                    .collect::<Vec<_>>()
+                                    .join(" ")
+                            };
+                            vec![
+                                g.name.clone(),
+                                status.to_string(),
+                                c.agent.clone(),
+                                c.path.clone(),
+                                changes,
+                            ]
+                        })
+                    }),
+                );
+                if groups
+                    .iter()
+                    .any(|g| g.status == DuplicateStatus::Identical)
+                {
+                    eprintln!("hint: identical copies can share one with convert --mode symlink");
+                }
+            }
+            Ok(0)
+        }
+        Commands::Watch => {
+            let _watcher = SkillsWatcher::start(Arc::new(PrintSink { json }))?;
+            eprintln!("Watching for skill changes, press Ctrl-C to stop");
+            loop {
+                std::thread::park();
+            }
+        }
+        Commands::Trash { action } => match action.unwrap_or(TrashAction::List) {
+            TrashAction::List => {
+                let entries = trash::list()?;
+                if json {
+                    print_json(&entries);
+                } else {
+                    print_trash(&entries);
+                }
+                Ok(0)
+            }
+            TrashAction::Restore { ids } => {
+                let mut restored = Vec::new();
+                let mut failed = false;
+                for id in &ids {
+                    match trash::restore(id) {
+                        Ok(entry) => restored.push(entry),
+                        Err(e) => {
+                            eprintln!("error: {}: {}", id, e);
+                            failed = true;
+                        }
+                    }
+                }
+                if json {
+                    print_json(&restored);
+                } else {
+                    for entry in &restored {
+                        println!("restored {}", entry.origin);
+                    }
+                }
+                Ok(if failed { EXIT_PARTIAL } else { 0 })
+            }
+            TrashAction::Purge { ids, all } => {
+                let purged = trash::purge((!all).then_some(ids.as_slice()))?;
+                if json {
+                    print_json(&purged);
+                } else {
+                    print_trash(&purged);
+                }
+                Ok(0)
+            }
+        },
+        Commands::Vault { action } => match action.unwrap_or(VaultAction::Status) {
+            VaultAction::Status => {
+                let status = vault::status()?;
+                if json {
+                    print_json(&status);
+                } else {
+                    println!("vault:   {}", status.path);
+                    println!(
+                        "status:  {}",
+                        if status.exists {
+                            "created"
+                        } else {
+                            "not created yet"
+                        }
+                    );
+                    println!("secrets: {}", status.secrets);
+                }
+                Ok(0)
+            }
+            VaultAction::Get { reference } => {
+                let secret = Vault::load()?.get(&reference)?;
+                if json {
+                    print_json(&secret);
+                } else {
+                    println!("{}", secret);
+                }
+                Ok(0)
+            }
+        },
+        Commands::Doctor {
+            fix: Some(fix),
+            links: only,
+        } => {
+            let fix = LinkFix::parse(&fix)?;
+            let broken = links::broken_links()?;
+            let mut repaired = Vec::new();
+            let mut failed = false;
+            for link in broken
+                .iter()
+                .filter(|link| only.is_empty() || only.contains(&link.path))
+            {
+                match links::repair(Path::new(&link.path), fix) {
+                    Ok(link) => repaired.push(link),
+                    Err(e) => {
+                        eprintln!("error: {}: {}", link.path, e);
+                        failed = true;
+                    }
+                }
+            }
+            for path in only
+                .iter()
+                .filter(|p| !broken.iter().any(|l| &&l.path == p))
+            {
+                eprintln!("error: {} is not a broken skill link", path);
+                failed = true;
+            }
+            if json {
+                print_json(&repaired);
+            } else {
+                for repaired in &repaired {
+                    println!("repaired {}", repaired.link.path);
+                    for path in &repaired.skipped {
+                        println!("  skipped {}: links outside the skill", path);
+                    }
+                }
+            }
+            Ok(if failed { EXIT_PARTIAL } else { 0 })
+        }
+        Commands::Doctor { fix: None, .. } => {
+            let report = doctor::run();
+            if json {
+                print_json(&report);
+            } else {
+                print_table(
+                    &["CHECK", "STATUS", "DETAILS"],
+                    report.checks.iter().map(|c| {
+                        let status = match c.status {
+                            CheckStatus::Ok => "ok",
+                            CheckStatus::Warning => "warning",
+                            CheckStatus::Error => "error",
+                        };
+                        vec![c.name.clone(), status.to_string(), c.message.clone()]
+                    }),
+                );
+                if !report.broken_links.is_empty() {
+                    println!();
+                    print_table(
+                        &["BROKEN LINK", "AGENT", "PROBLEM", "TARGET", "GLOBAL COPY"],
+                        report.broken_links.iter().map(|l| {
+                            let problem = match l.problem {
+                                LinkProblem::Dangling => "dangling",
+                                LinkProblem::OutsideStore => "outside store",
+                                LinkProblem::Loop => "loop",
+                            };
+                            vec![
+                                l.path.clone(),
+                                l.agent.clone(),
+                                problem.to_string(),
+                                l.target.clone(),
+                                l.store_copy.clone().unwrap_or_default(),
+                            ]
+                        }),
+                    );
+                    eprintln!("hint: repair them with doctor --fix relink|copy|delete");
+                }
+            }
+            Ok(if report.healthy() { 0 } else { EXIT_ERROR })
+        }
+        Commands::Export {
+            output,
+            manifest,
+            project,
+        } => {
+            let content = if manifest {
+                let project = project.as_deref().map(scope::project_root).transpose()?;
+                team::export(project.as_deref())?.to_toml()?
+            } else {
+                serde_json::to_string_pretty(&skills::export()?)
+                    .map_err(|e| SkillsError::Other(e.to_string()))?
+            };
+            match output {
+                Some(path) => fs::write(&path, content).map_err(|e| SkillsError::io(&path, e))?,
+                None => println!("{}", content),
+            }
+            Ok(0)
+        }
+        Commands::Sync {
+            file,
+            plan,
+            frozen,
+            project,
+        } => {
+            let (manifest, project) = team::load(&read_input(&file)?, project.as_deref())?;
+            if plan {
+                let plan = team::plan(&manifest, project.as_deref())?;
+                if json {
+                    print_json(&plan);
+                } else {
+                    print_plan(&plan);
+                }
+                return Ok(0);
+            }
+
+            let report = with_operation("sync", |op| {
+                team::apply(&manifest, project.as_deref(), frozen, op)
+            })?;
+            if json {
+                print_json(&report);
+            } else {
+                let mut rows: Vec<Vec<String>> = report
+                    .installed
+                    .iter()
+                    .map(|r| {
+                        vec![
+                            "install".to_string(),
+                            r.skill.clone().unwrap_or_else(|| r.source.clone()),
+                            outcome(r.error.as_ref(), "ok"),
+                        ]
+                    })
+                    .collect();
+                rows.extend(report.removed.iter().map(|r| {
+                    vec![
+                        "remove".to_string(),
+                        format!("{} ({})", r.skill, r.agent),
+                        outcome(r.error.as_ref(), "ok"),
+                    ]
+                }));
+                print_table(&["ACTION", "SKILL", "RESULT"], rows.into_iter());
+            }
+            let total = report.installed.len() + report.removed.len();
+            Ok(match report.failures() {
+                0 => 0,
+                n if n == total => EXIT_ERROR,
+                _ => EXIT_PARTIAL,
+            })
+        }
+        Commands::Import {
+            file,
+            agents,
+            global,
+            mode,
+            project,
+            backend,
+        } => {
+            let export: SkillsExport = serde_json::from_str(&read_input(&file)?)
+                .map_err(|e| SkillsError::InvalidInput(format!("Invalid export file: {}", e)))?;
+            let entries: Vec<_> = export
+                .skills
+                .iter()
+                .map(|s| s.to_import_entry(&agents, global))
+                .collect();
+
+            let mode = InstallMode::parse(&mode)?;
+            let project = project.as_deref().map(scope::project_root).transpose()?;
+            let backend = Backend::parse(backend.as_deref())?;
+            let results = with_operation("import", |op| {
+                skills::import(&entries, mode, project.as_deref(), backend, op)
+            })?;
+
+            if json {
+                print_json(&results);
+            } else {
+                print_table(
+                    &["SOURCE", "SKILL", "RESULT"],
+                    results.iter().map(|r| {
+                        let outcome = match (&r.report, &r.error) {
+                            (_, Some(e)) => format!("error: {}", e),
+                            (Some(report), None) => {
+                                format!("installed {}", report.installed.len())
+                            }
+                            (None, None) => String::new(),
+                        };
+                        vec![
+                            r.source.clone(),
+                            r.skill.clone().unwrap_or_default(),
+                            outcome,
+                        ]
+                    }),
+                );
+            }
+            let failed = results.iter().filter(|r| r.error.is_some()).count();
+            Ok(match failed {
+                0 => 0,
+                n if n == results.len() => EXIT_ERROR,
+                _ => EXIT_PARTIAL,
+            })
+        }
+    }
+}
+
+/// Reads `file`, or stdin for `-`.
+fn read_input(file: &str) -> Result<String> {
+    if file != "-" {
+        return fs::read_to_string(file).map_err(|e| SkillsError::io(file, e));
+    }
+    let mut content = String::new();
+    io::stdin()
+        .read_to_string(&mut content)
+        .map_err(|e| SkillsError::Io {
+            path: None,
+            message: e.to_string(),
+        })?;
+    Ok(content)
+}
+
+fn outcome(error: Option<&SkillsError>, ok: &str) -> String {
+    match error {
+        Some(e) => format!("error: {}", e),
+        None => ok.to_string(),
+    }
+}
+
+fn print_plan(plan: &SyncPlan) {
+    if plan.is_empty() {
+        println!("Up to date ({} skills match)", plan.unchanged);
+        return;
+    }
+    let steps = plan
+        .install
+        .iter()
+        .map(|s| ("install", s))
+        .chain(plan.upgrade.iter().map(|s| ("upgrade", s)));
+    let mut rows: Vec<Vec<String>> = steps
+        .map(|(action, s)| {
+            let mut agents = s.agents.clone();
+            if s.global {
+                agents.insert(0, "global".to_string());
+            }
+            vec![
+                action.to_string(),
+                s.skill.clone().unwrap_or_else(|| "*".to_string()),
+                agents.join(","),
+                s.source.clone(),
+                s.reason.clone(),
+            ]
+        })
+        .collect();
+    rows.extend(plan.remove.iter().map(|r| {
+        vec![
+            "remove".to_string(),
+            r.skill.clone(),
+            r.agent.clone(),
+            String::new(),
+            r.path.clone(),
+        ]
+    }));
+    print_table(
+        &["ACTION", "SKILL", "AGENTS", "SOURCE", "DETAILS"],
+        rows.into_iter(),
+    );
+}
+
+fn print_trash(entries: &[TrashEntry]) {
+    print_table(
+        &["ID", "AGENT", "DELETED", "ORIGIN"],
+        entries.iter().map(|e| {
+            let deleted = chrono::DateTime::from_timestamp(e.deleted_at as i64, 0)
+                .map(|at| {
+                    at.with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            vec![e.id.clone(), e.agent.clone(), deleted, e.origin.clone()]
+        }),
+    );
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("error: {}", e),
+    }
+}
+
+fn print_table(headers: &[&str], rows: impl Iterator<Item = Vec<String>>) {
+    let rows: Vec<Vec<String>> = rows.collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", format_row(headers.to_vec()));
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
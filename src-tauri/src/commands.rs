@@ -0,0 +1,562 @@
+//! Tauri commands: thin wrappers that parse frontend arguments, start an
+//! operation and hand off to [`crate::skills`].
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use tauri::{Emitter, Manager};
+
+use crate::agents::{self, AgentDefinition, AgentOverride, AgentRegistry};
+use crate::config_history::{ConfigChange, ConfigVersion};
+use crate::doctor::{self, DoctorReport};
+use crate::duplicates::{self, DuplicateGroup};
+use crate::error::{Result, SkillsError};
+use crate::installer::convert::Converted;
+use crate::installer::{
+    Backend, ImportEntry, ImportResult, InstallMode, InstallReport, InstallRequest, RemoveReport,
+    RemoveRequest,
+};
+use crate::links::{self, LinkFix, RepairedLink};
+use crate::operations::{
+    self, Operation, OperationLog, ProgressEvent, ProgressSink, PROGRESS_EVENT,
+};
+use crate::scope;
+use crate::settings;
+use crate::skills::{
+    self, GlobalSkillInfo, Skill, SkillConfigResponse, SkillUpdateInfo, SkillsExport,
+};
+use crate::source_manager::{self, MigrationReport, SourceRecord};
+use crate::team::{self, SyncPlan, SyncReport};
+use crate::trash::{self, TrashEntry};
+use crate::vault::{self, VaultStatus};
+use crate::verify::{self, SkillVerification};
+use crate::watcher::{ChangeSink, SkillsChanged, SkillsWatcher, CHANGED_EVENT};
+use crate::workspaces;
+
+// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+#[tauri::command]
+fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
+/// Forwards operation progress to the frontend as Tauri events.
+struct TauriSink(tauri::AppHandle);
+
+impl ProgressSink for TauriSink {
+    fn emit(&self, event: &ProgressEvent) {
+        let _ = self.0.emit(PROGRESS_EVENT, event);
+    }
+}
+
+/// Forwards skill changes found by the watcher to the frontend.
+struct TauriChangeSink(tauri::AppHandle);
+
+impl ChangeSink for TauriChangeSink {
+    fn emit(&self, event: &SkillsChanged) {
+        let _ = self.0.emit(CHANGED_EVENT, event);
+    }
+}
+
+/// Points the watcher at the current agents and workspaces.
+fn refresh_watcher(app: &tauri::AppHandle) {
+    if let Some(watcher) = app.try_state::<SkillsWatcher>() {
+        if let Err(e) = watcher.refresh() {
+            eprintln!("Failed to update watched directories: {}", e);
+        }
+    }
+}
+
+fn start_operation(app: &tauri::AppHandle, kind: &str, id: Option<String>) -> Operation {
+    Operation::start(kind, id, Arc::new(TauriSink(app.clone())))
+        .with_timeout(settings::timeout_for(kind))
+}
+
+#[tauri::command]
+fn get_local_skills() -> Result<Vec<Skill>> {
+    skills::local_skills()
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn install_skill(
+    app: tauri::AppHandle,
+    id: String,
+    skill: Option<String>,
+    global: bool,
+    agents: Vec<String>,
+    auto_confirm: bool,
+    install_mode: String,
+    project: Option<String>,
+    backend: Option<String>,
+    frozen: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<InstallReport> {
+    let op = start_operation(&app, "install", operation_id);
+    op.log(format!(
+        "Starting installation: {} (skill={:?}, mode={}) with global={}, agents={:?}, auto_confirm={}, project={:?}",
+        id, skill, install_mode, global, agents, auto_confirm, project
+    ));
+
+    let result = (|| {
+        let request = InstallRequest {
+            source: id.clone(),
+            reference: None,
+            skill,
+            global,
+            agents,
+            mode: InstallMode::parse(&install_mode)?,
+            project: project.as_deref().map(scope::project_root).transpose()?,
+            auto_confirm,
+            frozen: frozen.unwrap_or(false),
+        };
+        skills::install(&request, Backend::parse(backend.as_deref())?, &op)
+    })();
+
+    op.finish(&result);
+    result
+}
+
+/// Installed skills with their agents, sources and redacted configs.
+#[tauri::command]
+async fn export_skills() -> Result<SkillsExport> {
+    skills::export()
+}
+
+#[tauri::command]
+async fn import_skills(
+    app: tauri::AppHandle,
+    entries: Vec<ImportEntry>,
+    install_mode: String,
+    project: Option<String>,
+    backend: Option<String>,
+    operation_id: Option<String>,
+) -> Result<Vec<ImportResult>> {
+    let op = start_operation(&app, "import", operation_id);
+    op.log(format!("Importing {} skill sources", entries.len()));
+
+    let result = (|| {
+        let mode = InstallMode::parse(&install_mode)?;
+        let project = project.as_deref().map(scope::project_root).transpose()?;
+        let backend = Backend::parse(backend.as_deref())?;
+        skills::import(&entries, mode, project.as_deref(), backend, &op)
+    })();
+
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+async fn uninstall_skill(
+    id: String,
+    agents: Vec<String>,
+    project: Option<String>,
+    cascade: Option<bool>,
+) -> Result<String> {
+    skills::uninstall(&id, &agents, project.as_deref(), cascade.unwrap_or(false))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn remove_skills(
+    app: tauri::AppHandle,
+    skill_ids: Vec<String>,
+    global: bool,
+    agents: Vec<String>,
+    remove_all: bool,
+    auto_confirm: bool,
+    project: Option<String>,
+    backend: Option<String>,
+    cascade: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<RemoveReport> {
+    let op = start_operation(&app, "remove_skills", operation_id);
+    op.log(format!(
+        "Starting removal: skill_ids={:?}, global={}, agents={:?}, remove_all={}, auto_confirm={}, project={:?}",
+        skill_ids, global, agents, remove_all, auto_confirm, project
+    ));
+
+    let result = (|| {
+        let request = RemoveRequest {
+            skill_ids,
+            global,
+            agents,
+            remove_all,
+            project: project.as_deref().map(scope::project_root).transpose()?,
+            auto_confirm,
+            cascade: cascade.unwrap_or(false),
+        };
+        skills::remove(&request, Backend::parse(backend.as_deref())?, &op)
+    })();
+
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+async fn convert_install_mode(
+    app: tauri::AppHandle,
+    skill: String,
+    agent: String,
+    mode: String,
+    project: Option<String>,
+    operation_id: Option<String>,
+) -> Result<Converted> {
+    let op = start_operation(&app, "convert", operation_id);
+    op.log(format!(
+        "Converting {} for {} to {} (project={:?})",
+        skill, agent, mode, project
+    ));
+
+    let result = (|| {
+        let mode = InstallMode::parse(&mode)?;
+        let project = project.as_deref().map(scope::project_root).transpose()?;
+        skills::convert_install_mode(&skill, &agent, mode, project.as_deref(), &op)
+    })();
+
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+fn list_global_skills() -> Result<Vec<GlobalSkillInfo>> {
+    skills::global_skills()
+}
+
+#[tauri::command]
+async fn remove_global_skill(
+    app: tauri::AppHandle,
+    id: String,
+    backend: Option<String>,
+    operation_id: Option<String>,
+) -> Result<RemoveReport> {
+    let op = start_operation(&app, "remove_global", operation_id);
+    op.log(format!("Removing global skill: {}", id));
+
+    let result = Backend::parse(backend.as_deref())
+        .and_then(|backend| skills::remove_global(&id, backend, &op));
+
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+async fn get_skill_config(
+    id: String,
+    agent: String,
+    project: Option<String>,
+) -> Result<SkillConfigResponse> {
+    skills::skill_config(&id, &agent, project.as_deref())
+}
+
+#[tauri::command]
+fn save_skill_config(
+    id: String,
+    agent: String,
+    config: String,
+    project: Option<String>,
+) -> Result<ConfigVersion> {
+    skills::save_skill_config(&id, &agent, &config, project.as_deref())
+}
+
+#[tauri::command]
+fn list_config_history(
+    id: String,
+    agent: String,
+    project: Option<String>,
+) -> Result<Vec<ConfigVersion>> {
+    skills::config_history(&id, &agent, project.as_deref())
+}
+
+#[tauri::command]
+fn restore_config_version(
+    id: String,
+    agent: String,
+    version: u64,
+    project: Option<String>,
+) -> Result<ConfigVersion> {
+    skills::restore_config_version(&id, &agent, version, project.as_deref())
+}
+
+#[tauri::command]
+fn diff_config_versions(
+    id: String,
+    agent: String,
+    from: u64,
+    to: u64,
+    project: Option<String>,
+) -> Result<Vec<ConfigChange>> {
+    skills::diff_config_versions(&id, &agent, from, to, project.as_deref())
+}
+
+#[tauri::command]
+fn get_vault_status() -> Result<VaultStatus> {
+    vault::status()
+}
+
+/// Creates the vault with `passphrase` the first time.
+#[tauri::command]
+async fn unlock_vault(passphrase: String) -> Result<VaultStatus> {
+    vault::unlock(&passphrase)
+}
+
+#[tauri::command]
+fn lock_vault() -> Result<VaultStatus> {
+    vault::lock();
+    vault::status()
+}
+
+#[tauri::command]
+async fn check_skill_updates(
+    app: tauri::AppHandle,
+    skills: Vec<Skill>,
+    operation_id: Option<String>,
+) -> Result<Vec<SkillUpdateInfo>> {
+    let op = start_operation(&app, "check_updates", operation_id);
+    let result = skills::check_updates(skills, &op);
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+async fn update_skill_repo(
+    app: tauri::AppHandle,
+    id: String,
+    agent: String,
+    project: Option<String>,
+    operation_id: Option<String>,
+) -> Result<String> {
+    let op = start_operation(&app, "update", operation_id);
+    op.log(format!(
+        "Updating {} for {} (project={:?})",
+        id, agent, project
+    ));
+
+    let result = skills::update_skill(&id, &agent, project.as_deref(), &op);
+
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+async fn verify_skills() -> Result<Vec<SkillVerification>> {
+    verify::verify_skills()
+}
+
+#[tauri::command]
+async fn find_duplicates() -> Result<Vec<DuplicateGroup>> {
+    duplicates::find_duplicates()
+}
+
+#[tauri::command]
+fn export_manifest(project: Option<String>) -> Result<String> {
+    let project = project.as_deref().map(scope::project_root).transpose()?;
+    team::export(project.as_deref())?.to_toml()
+}
+
+#[tauri::command]
+fn plan_sync(manifest: String, project: Option<String>) -> Result<SyncPlan> {
+    let (manifest, project) = team::load(&manifest, project.as_deref())?;
+    team::plan(&manifest, project.as_deref())
+}
+
+#[tauri::command]
+async fn apply_sync(
+    app: tauri::AppHandle,
+    manifest: String,
+    project: Option<String>,
+    frozen: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<SyncReport> {
+    let op = start_operation(&app, "sync", operation_id);
+    op.log("Syncing with team manifest");
+
+    let result = team::load(&manifest, project.as_deref()).and_then(|(manifest, project)| {
+        team::apply(&manifest, project.as_deref(), frozen.unwrap_or(false), &op)
+    });
+
+    op.finish(&result);
+    result
+}
+
+#[tauri::command]
+fn get_operation_log(id: String) -> Result<OperationLog> {
+    operations::get_operation_log(&id)
+        .ok_or_else(|| SkillsError::InvalidInput(format!("Unknown operation: {}", id)))
+}
+
+#[tauri::command]
+fn cancel_operation(id: String) -> Result<()> {
+    operations::cancel_operation(&id)
+}
+
+#[tauri::command]
+fn get_operation_timeouts() -> Result<BTreeMap<String, u64>> {
+    settings::timeouts()
+}
+
+#[tauri::command]
+fn set_operation_timeout(kind: String, seconds: Option<u64>) -> Result<()> {
+    settings::set_timeout(&kind, seconds)
+}
+
+#[tauri::command]
+fn list_trash() -> Result<Vec<TrashEntry>> {
+    trash::list()
+}
+
+#[tauri::command]
+fn restore_from_trash(id: String) -> Result<TrashEntry> {
+    trash::restore(&id)
+}
+
+/// Deletes the given entries for good, or the whole trash without `ids`.
+#[tauri::command]
+fn purge_trash(ids: Option<Vec<String>>) -> Result<Vec<TrashEntry>> {
+    trash::purge(ids.as_deref())
+}
+
+#[tauri::command]
+fn get_trash_retention() -> u32 {
+    settings::trash_retention_days()
+}
+
+#[tauri::command]
+fn set_trash_retention(days: Option<u32>) -> Result<()> {
+    settings::set_trash_retention_days(days)
+}
+
+#[tauri::command]
+async fn run_doctor() -> DoctorReport {
+    doctor::run()
+}
+
+#[tauri::command]
+async fn repair_link(path: String, fix: String) -> Result<RepairedLink> {
+    links::repair(Path::new(&path), LinkFix::parse(&fix)?)
+}
+
+#[tauri::command]
+fn list_operations() -> Vec<OperationLog> {
+    operations::list_operations()
+}
+
+#[tauri::command]
+fn get_skill_provenance(
+    id: String,
+    agent: String,
+    project: Option<String>,
+) -> Result<Option<SourceRecord>> {
+    skills::skill_provenance(&id, &agent, project.as_deref())
+}
+
+#[tauri::command]
+fn get_migration_report() -> Result<Option<MigrationReport>> {
+    source_manager::migration_report()
+}
+
+#[tauri::command]
+fn list_agents() -> Result<Vec<AgentDefinition>> {
+    Ok(AgentRegistry::load()?.all().to_vec())
+}
+
+#[tauri::command]
+fn upsert_agent(app: tauri::AppHandle, agent: AgentOverride) -> Result<AgentDefinition> {
+    let result = agents::upsert_agent(agent);
+    refresh_watcher(&app);
+    result
+}
+
+#[tauri::command]
+fn remove_agent(app: tauri::AppHandle, id: String) -> Result<()> {
+    let result = agents::remove_agent(&id);
+    refresh_watcher(&app);
+    result
+}
+
+#[tauri::command]
+fn list_workspaces() -> Result<Vec<String>> {
+    Ok(workspaces::list_workspaces()?
+        .iter()
+        .map(|root| root.to_string_lossy().to_string())
+        .collect())
+}
+
+#[tauri::command]
+fn add_workspace(app: tauri::AppHandle, path: String) -> Result<String> {
+    let result = workspaces::add_workspace(&path).map(|root| root.to_string_lossy().to_string());
+    refresh_watcher(&app);
+    result
+}
+
+#[tauri::command]
+fn remove_workspace(app: tauri::AppHandle, path: String) -> Result<()> {
+    let result = workspaces::remove_workspace(&path);
+    refresh_watcher(&app);
+    result
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            // The app still works without live updates, so don't fail startup
+            match SkillsWatcher::start(Arc::new(TauriChangeSink(app.handle().clone()))) {
+                Ok(watcher) => {
+                    app.manage(watcher);
+                }
+                Err(e) => eprintln!("Skills watcher disabled: {}", e),
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            get_local_skills,
+            install_skill,
+            uninstall_skill,
+            remove_skills,
+            list_global_skills,
+            remove_global_skill,
+            get_skill_config,
+            save_skill_config,
+            list_config_history,
+            restore_config_version,
+            diff_config_versions,
+            get_vault_status,
+            unlock_vault,
+            lock_vault,
+            check_skill_updates,
+            update_skill_repo,
+            list_agents,
+            upsert_agent,
+            remove_agent,
+            list_workspaces,
+            add_workspace,
+            remove_workspace,
+            export_skills,
+            import_skills,
+            get_operation_log,
+            list_operations,
+            cancel_operation,
+            get_operation_timeouts,
+            set_operation_timeout,
+            get_skill_provenance,
+            get_migration_report,
+            export_manifest,
+            plan_sync,
+            apply_sync,
+            verify_skills,
+            find_duplicates,
+            list_trash,
+            restore_from_trash,
+            purge_trash,
+            get_trash_retention,
+            set_trash_retention,
+            run_doctor,
+            repair_link,
+            convert_install_mode
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
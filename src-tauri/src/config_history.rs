@@ -0,0 +1,275 @@
+//! Saved versions of each skill's `skill.config.json`.
+//!
+//! Configs are written through a temp file renamed over the old one, and
+//! every version written is kept in `<data dir>/config-history/<key>.json`,
+//! where the key is a hash of the config's path. Only the newest
+//! [`settings::config_history_limit`] versions are kept. A config edited by
+//! hand since the last save is recorded before it is overwritten, so it can
+//! be rolled back to as well.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Result, SkillsError};
+use crate::paths;
+use crate::settings;
+use crate::util::{self, now_secs};
+
+const HISTORY_DIR: &str = "config-history";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfigVersion {
+    /// Increases with every save of the same config
+    pub version: u64,
+    /// Seconds since the Unix epoch
+    pub saved_at: u64,
+    pub content: String,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct HistoryFile {
+    config: String,
+    /// Oldest first
+    #[serde(default)]
+    versions: Vec<ConfigVersion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One value that differs between two versions.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConfigChange {
+    /// JSON pointer of the value (`""` for the whole config)
+    pub pointer: String,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+fn history_path(config: &Path) -> Result<PathBuf> {
+    let hash = Sha256::digest(config.to_string_lossy().as_bytes());
+    Ok(paths::data_dir()?
+        .join(HISTORY_DIR)
+        .join(format!("{}.json", util::to_hex(&hash[..16]))))
+}
+
+fn load(config: &Path) -> Result<HistoryFile> {
+    let path = history_path(config)?;
+    match fs::read_to_string(&path) {
+        Ok(content) => {
+            serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HistoryFile {
+            config: config.to_string_lossy().to_string(),
+            versions: Vec::new(),
+        }),
+        Err(e) => Err(SkillsError::io(&path, e)),
+    }
+}
+
+impl HistoryFile {
+    fn push(&mut self, content: String) -> ConfigVersion {
+        let version = ConfigVersion {
+            version: self.versions.last().map_or(1, |v| v.version + 1),
+            saved_at: now_secs(),
+            content,
+        };
+        self.versions.push(version.clone());
+        version
+    }
+
+    fn save(&mut self, config: &Path) -> Result<()> {
+        let limit = settings::config_history_limit().max(1);
+        let excess = self.versions.len().saturating_sub(limit);
+        self.versions.drain(..excess);
+
+        let path = history_path(config)?;
+        paths::ensure_parent(&path)?;
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| SkillsError::Other(e.to_string()))?;
+        util::write_atomic(&path, content.as_bytes())
+    }
+}
+
+/// Replaces `config` with `content` and records it as a new version.
+pub fn write(config: &Path, content: &str) -> Result<ConfigVersion> {
+    // Held across the read and rewrite, so the app and the command line
+    // saving at once don't drop each other's versions
+    let _lock = util::lock(&history_path(config)?.with_extension("lock"), true)?;
+    let mut history = load(config)?;
+    if let Ok(current) = fs::read_to_string(config) {
+        if history.versions.last().map(|v| &v.content) != Some(&current) {
+            history.push(current);
+        }
+    }
+    util::write_atomic(config, content.as_bytes())?;
+    let version = history.push(content.to_string());
+    if let Err(e) = history.save(config) {
+        eprintln!("Failed to record history of {}: {}", config.display(), e);
+    }
+    Ok(version)
+}
+
+/// Recorded versions of `config`, newest first.
+pub fn list(config: &Path) -> Result<Vec<ConfigVersion>> {
+    let mut versions = load(config)?.versions;
+    versions.reverse();
+    Ok(versions)
+}
+
+pub fn version(config: &Path, version: u64) -> Result<ConfigVersion> {
+    load(config)?
+        .versions
+        .into_iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| {
+            SkillsError::InvalidInput(format!("{} has no version {}", config.display(), version))
+        })
+}
+
+/// What changed from version `from` to version `to` of `config`.
+pub fn diff(config: &Path, from: u64, to: u64) -> Result<Vec<ConfigChange>> {
+    let parse = |v: ConfigVersion| -> Result<Value> {
+        serde_json::from_str(&v.content).map_err(|e| {
+            SkillsError::invalid_config(config, format!("version {}: {}", v.version, e))
+        })
+    };
+    let old = parse(version(config, from)?)?;
+    let new = parse(version(config, to)?)?;
+    let mut changes = Vec::new();
+    diff_values("", &old, &new, &mut changes);
+    Ok(changes)
+}
+
+fn diff_values(pointer: &str, old: &Value, new: &Value, changes: &mut Vec<ConfigChange>) {
+    let child = |key: &str| format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                match new.get(key) {
+                    Some(new_value) => diff_values(&child(key), old_value, new_value, changes),
+                    None => changes.push(ConfigChange {
+                        pointer: child(key),
+                        kind: ChangeKind::Removed,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    }),
+                }
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                changes.push(ConfigChange {
+                    pointer: child(key),
+                    kind: ChangeKind::Added,
+                    old: None,
+                    new: Some(new_value.clone()),
+                });
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let pointer = child(&i.to_string());
+                match (old.get(i), new.get(i)) {
+                    (Some(o), Some(n)) => diff_values(&pointer, o, n, changes),
+                    (Some(o), None) => changes.push(ConfigChange {
+                        pointer,
+                        kind: ChangeKind::Removed,
+                        old: Some(o.clone()),
+                        new: None,
+                    }),
+                    (None, Some(n)) => changes.push(ConfigChange {
+                        pointer,
+                        kind: ChangeKind::Added,
+                        old: None,
+                        new: Some(n.clone()),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(ConfigChange {
+            pointer: pointer.to_string(),
+            kind: ChangeKind::Changed,
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn changes(old: Value, new: Value) -> Vec<(String, ChangeKind)> {
+        let mut changes = Vec::new();
+        diff_values("", &old, &new, &mut changes);
+        changes.into_iter().map(|c| (c.pointer, c.kind)).collect()
+    }
+
+    #[test]
+    fn equal_configs_have_no_changes() {
+        let config = json!({ "a": 1, "nested": { "b": [1, 2] } });
+        assert!(changes(config.clone(), config).is_empty());
+    }
+
+    #[test]
+    fn added_removed_and_changed_keys() {
+        let diff = changes(
+            json!({ "keep": 1, "gone": true, "edit": "old" }),
+            json!({ "keep": 1, "edit": "new", "fresh": null }),
+        );
+        assert_eq!(
+            diff,
+            [
+                ("/edit".to_string(), ChangeKind::Changed),
+                ("/gone".to_string(), ChangeKind::Removed),
+                ("/fresh".to_string(), ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_values_and_arrays() {
+        let diff = changes(
+            json!({ "server": { "port": 80, "hosts": ["a", "b"] } }),
+            json!({ "server": { "port": 8080, "hosts": ["a", "c", "d"] } }),
+        );
+        assert_eq!(
+            diff,
+            [
+                ("/server/hosts/1".to_string(), ChangeKind::Changed),
+                ("/server/hosts/2".to_string(), ChangeKind::Added),
+                ("/server/port".to_string(), ChangeKind::Changed),
+            ]
+        );
+    }
+
+    #[test]
+    fn pointers_escape_keys_and_types_can_change() {
+        let diff = changes(
+            json!({ "a/b": 1, "c~d": {} }),
+            json!({ "a/b": "1", "c~d": [] }),
+        );
+        assert_eq!(
+            diff,
+            [
+                ("/a~1b".to_string(), ChangeKind::Changed),
+                ("/c~0d".to_string(), ChangeKind::Changed),
+            ]
+        );
+        assert_eq!(
+            changes(json!(1), json!({ "a": 1 })),
+            [(String::new(), ChangeKind::Changed)]
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Content hashes of skill directories.
+//!
+//! Used to recognise a skill after it was moved or renamed, and to tell
+//! whether it was changed since it was installed. Only file paths and
+//! contents count; timestamps, permissions and `.git` are ignored.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::error::{Result, SkillsError};
+use crate::util;
+
+/// Calls `f` with the `/`-separated relative path and contents of every file
+/// under `dir`, in path order. Symlinks inside `dir` aren't followed, so
+/// nothing outside the skill is read; `dir` itself may be a link.
+fn for_each_file(dir: &Path, mut f: impl FnMut(&str, &[u8])) -> Result<()> {
+    let walker = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git");
+
+    for entry in walker {
+        let entry = entry.map_err(|e| SkillsError::Io {
+            path: Some(e.path().unwrap_or(dir).to_path_buf()),
+            message: e.to_string(),
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        // Same digest on every platform
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let content = fs::read(entry.path()).map_err(|e| SkillsError::io(entry.path(), e))?;
+        f(&relative, &content);
+    }
+    Ok(())
+}
+
+fn to_hex(hash: &[u8]) -> String {
+    format!("sha256:{}", util::to_hex(hash))
+}
+
+/// `sha256:<hex>` over every file under `dir`, in path order.
+pub fn dir_digest(dir: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for_each_file(dir, |relative, content| {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(content);
+    })?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// `sha256:<hex>` of each file under `dir`, by relative path, so changes can
+/// be narrowed down to the files that were modified, added or deleted.
+pub fn file_digests(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for_each_file(dir, |relative, content| {
+        files.insert(relative.to_string(), to_hex(&Sha256::digest(content)));
+    })?;
+    Ok(files)
+}
@@ -0,0 +1,150 @@
+//! Health checks for the tools and files the manager depends on, and the
+//! skill links it created.
+
+use std::fs;
+use std::process::{Command, Stdio};
+
+use crate::agents::AgentRegistry;
+use crate::error::SkillsError;
+use crate::links::{self, BrokenLink};
+use crate::paths;
+use crate::settings;
+use crate::source_manager::SourceIndex;
+use crate::workspaces;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl Check {
+    fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Check {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn from_result<T>(
+        name: &str,
+        result: Result<T, SkillsError>,
+        ok: impl FnOnce(T) -> String,
+    ) -> Self {
+        match result {
+            Ok(value) => Check::new(name, CheckStatus::Ok, ok(value)),
+            Err(e) => Check::new(name, CheckStatus::Error, e.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+    /// Skill links that need repairing with [`links::repair`]
+    pub broken_links: Vec<BrokenLink>,
+}
+
+impl DoctorReport {
+    /// True when no check failed; warnings don't count.
+    pub fn healthy(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Error)
+    }
+}
+
+pub fn run() -> DoctorReport {
+    let broken_links = links::broken_links();
+    let checks = vec![
+        git_check(),
+        tool_check("npx", CheckStatus::Warning),
+        Check::from_result("agents", AgentRegistry::load(), |registry| {
+            format!("{} agents enabled", registry.enabled().count())
+        }),
+        Check::from_result("settings", settings::load(), |_| "settings.json ok".into()),
+        Check::from_result("workspaces", workspaces::list_workspaces(), |roots| {
+            format!("{} workspaces", roots.len())
+        }),
+        Check::from_result("provenance", SourceIndex::load(), |_| {
+            "skill_sources.json ok".into()
+        }),
+        data_dir_check(),
+        links_check(&broken_links),
+    ];
+    DoctorReport {
+        checks,
+        broken_links: broken_links.unwrap_or_default(),
+    }
+}
+
+/// `missing` is how bad it is when `command` isn't on PATH.
+fn tool_check(command: &str, missing: CheckStatus) -> Check {
+    let output = Command::new(command)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output();
+    match output {
+        Ok(output) if output.status.success() => Check::new(
+            command,
+            CheckStatus::Ok,
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ),
+        Ok(output) => Check::new(
+            command,
+            missing,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ),
+        Err(e) => Check::new(command, missing, SkillsError::spawn(command, e).to_string()),
+    }
+}
+
+/// Git is built in, so this reports which transports it can use.
+fn git_check() -> Check {
+    let version = git2::Version::get();
+    let (major, minor, rev) = version.libgit2_version();
+    let mut message = format!("built-in libgit2 {}.{}.{}", major, minor, rev);
+    let missing: Vec<&str> = [("https", version.https()), ("ssh", version.ssh())]
+        .into_iter()
+        .filter(|(_, supported)| !supported)
+        .map(|(transport, _)| transport)
+        .collect();
+    if missing.is_empty() {
+        return Check::new("git", CheckStatus::Ok, message);
+    }
+    message.push_str(&format!(", without {} support", missing.join("/")));
+    Check::new("git", CheckStatus::Warning, message)
+}
+
+fn links_check(broken: &Result<Vec<BrokenLink>, SkillsError>) -> Check {
+    match broken {
+        Ok(broken) if broken.is_empty() => {
+            Check::new("links", CheckStatus::Ok, "no broken skill links")
+        }
+        Ok(broken) => Check::new(
+            "links",
+            CheckStatus::Warning,
+            format!("{} broken skill links", broken.len()),
+        ),
+        Err(e) => Check::new("links", CheckStatus::Error, e.to_string()),
+    }
+}
+
+fn data_dir_check() -> Check {
+    let result = paths::data_dir().and_then(|dir| {
+        fs::create_dir_all(&dir).map_err(|e| SkillsError::io(&dir, e))?;
+        tempfile::NamedTempFile::new_in(&dir).map_err(|e| SkillsError::io(&dir, e))?;
+        Ok(dir)
+    });
+    Check::from_result("data_dir", result, |dir| {
+        format!("{} is writable", dir.display())
+    })
+}
@@ -0,0 +1,122 @@
+//! Finds skills installed as separate copies for several agents and tells
+//! whether the copies still match.
+//!
+//! Copies are grouped by skill name and source, within the home directory
+//! or a single project. Links don't count as copies: they already share
+//! one. Within a group the copy most others agree with is the reference,
+//! and every other copy is diffed against it file by file.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::digest;
+use crate::error::Result;
+use crate::scope::SkillScope;
+use crate::skills::{self, Skill};
+use crate::verify;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateStatus {
+    /// Every copy has the same files; they could be one linked copy
+    Identical,
+    /// Some copies have diverged from the others
+    Drifted,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DuplicateCopy {
+    pub agent: String,
+    pub scope: SkillScope,
+    pub path: String,
+    pub digest: String,
+    /// The copy the others are compared with
+    pub reference: bool,
+    /// Files whose content differs from the reference copy
+    pub modified: Vec<String>,
+    /// Files the reference copy doesn't have
+    pub added: Vec<String>,
+    /// Files of the reference copy this one doesn't have
+    pub deleted: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DuplicateGroup {
+    /// Directory name of the skill
+    pub name: String,
+    pub source: Option<String>,
+    /// Project the copies are in; `None` for the home directory
+    pub project: Option<String>,
+    pub status: DuplicateStatus,
+    pub copies: Vec<DuplicateCopy>,
+}
+
+/// Every skill with more than one real copy, in the home directory and
+/// registered workspaces.
+pub fn find_duplicates() -> Result<Vec<DuplicateGroup>> {
+    let mut groups: BTreeMap<(Option<String>, String, Option<String>), Vec<Skill>> =
+        BTreeMap::new();
+    for skill in skills::local_skills()? {
+        if skill.is_symlink {
+            continue;
+        }
+        let key = (
+            skill.scope.project_path().map(str::to_string),
+            skill.id.clone(),
+            skill.source.clone(),
+        );
+        groups.entry(key).or_default().push(skill);
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1)
+        .map(|((project, name, source), copies)| compare(name, source, project, copies))
+        .collect()
+}
+
+fn compare(
+    name: String,
+    source: Option<String>,
+    project: Option<String>,
+    copies: Vec<Skill>,
+) -> Result<DuplicateGroup> {
+    let mut files = Vec::new();
+    for copy in &copies {
+        files.push(digest::file_digests(Path::new(&copy.path))?);
+    }
+    // The copy most others agree with; the first one (the global store
+    // copy when there is one) on a tie
+    let reference = (0..files.len())
+        .max_by_key(|&i| {
+            let agreeing = files.iter().filter(|other| **other == files[i]).count();
+            (agreeing, std::cmp::Reverse(i))
+        })
+        .unwrap_or_default();
+
+    let mut status = DuplicateStatus::Identical;
+    let mut result = Vec::new();
+    for (i, copy) in copies.into_iter().enumerate() {
+        let (modified, added, deleted) = verify::diff(&files[reference], &files[i]);
+        if !(modified.is_empty() && added.is_empty() && deleted.is_empty()) {
+            status = DuplicateStatus::Drifted;
+        }
+        result.push(DuplicateCopy {
+            digest: digest::dir_digest(Path::new(&copy.path))?,
+            agent: copy.agent,
+            scope: copy.scope,
+            path: copy.path,
+            reference: i == reference,
+            modified,
+            added,
+            deleted,
+        });
+    }
+    Ok(DuplicateGroup {
+        name,
+        source,
+        project,
+        status,
+        copies: result,
+    })
+}
@@ -0,0 +1,349 @@
+//! Error type returned by every command.
+//!
+//! Serializes as `{ kind, message, context: { path, agent, skill } }` so the
+//! frontend can tell failures apart and offer a fix for the common ones.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::ser::SerializeStruct;
+
+use crate::operations::Interrupted;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillsError {
+    UnknownAgent {
+        agent: String,
+    },
+    SkillNotFound {
+        skill: String,
+        path: Option<PathBuf>,
+    },
+    NotFound {
+        path: PathBuf,
+    },
+    PermissionDenied {
+        path: PathBuf,
+        message: String,
+    },
+    Io {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// A config or state file that exists but can't be parsed.
+    InvalidConfig {
+        path: PathBuf,
+        message: String,
+    },
+    InvalidInput(String),
+    /// A git operation on the repository at `path` failed.
+    Git {
+        path: PathBuf,
+        action: String,
+        message: String,
+    },
+    CommandNotFound {
+        command: String,
+    },
+    CommandFailed {
+        command: String,
+        message: String,
+    },
+    Network {
+        url: String,
+        message: String,
+    },
+    /// A skill's content differs from the digest recorded for it.
+    IntegrityMismatch {
+        skill: String,
+        expected: String,
+        actual: String,
+    },
+    /// A skill config that isn't valid JSON or doesn't match the skill's schema.
+    InvalidSkillConfig {
+        path: PathBuf,
+        errors: Vec<FieldError>,
+    },
+    /// A secret is needed but the vault's passphrase hasn't been given.
+    VaultLocked {
+        /// Environment variable that can hold the passphrase
+        env: String,
+    },
+    /// A skill copy that other agents' symlinks still point to.
+    HasDependents {
+        path: PathBuf,
+        dependents: Vec<PathBuf>,
+    },
+    Cancelled,
+    TimedOut {
+        seconds: u64,
+    },
+    Other(String),
+}
+
+pub type Result<T> = std::result::Result<T, SkillsError>;
+
+/// One problem with a value, at a JSON pointer into the document (`""` for
+/// the whole document).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FieldError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl SkillsError {
+    /// Classifies an I/O failure on `path`.
+    pub fn io(path: impl AsRef<Path>, err: io::Error) -> Self {
+        let path = path.as_ref().to_path_buf();
+        match err.kind() {
+            io::ErrorKind::NotFound => SkillsError::NotFound { path },
+            io::ErrorKind::PermissionDenied => SkillsError::PermissionDenied {
+                path,
+                message: err.to_string(),
+            },
+            _ => SkillsError::Io {
+                path: Some(path),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    pub fn invalid_config(path: impl AsRef<Path>, err: impl fmt::Display) -> Self {
+        SkillsError::InvalidConfig {
+            path: path.as_ref().to_path_buf(),
+            message: err.to_string(),
+        }
+    }
+
+    /// Error for a command that couldn't be started.
+    pub fn spawn(command: &str, err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::NotFound => SkillsError::CommandNotFound {
+                command: command.to_string(),
+            },
+            _ => SkillsError::CommandFailed {
+                command: command.to_string(),
+                message: err.to_string(),
+            },
+        }
+    }
+
+    pub fn skill_not_found(skill: impl Into<String>, path: Option<&Path>) -> Self {
+        SkillsError::SkillNotFound {
+            skill: skill.into(),
+            path: path.map(Path::to_path_buf),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SkillsError::UnknownAgent { .. } => "unknown_agent",
+            SkillsError::SkillNotFound { .. } => "skill_not_found",
+            SkillsError::NotFound { .. } => "not_found",
+            SkillsError::PermissionDenied { .. } => "permission_denied",
+            SkillsError::Io { .. } => "io",
+            SkillsError::InvalidConfig { .. } => "invalid_config",
+            SkillsError::InvalidInput(_) => "invalid_input",
+            SkillsError::Git { .. } => "git",
+            SkillsError::CommandNotFound { .. } => "command_not_found",
+            SkillsError::CommandFailed { .. } => "command_failed",
+            SkillsError::Network { .. } => "network",
+            SkillsError::IntegrityMismatch { .. } => "integrity_mismatch",
+            SkillsError::InvalidSkillConfig { .. } => "invalid_skill_config",
+            SkillsError::VaultLocked { .. } => "vault_locked",
+            SkillsError::HasDependents { .. } => "has_dependents",
+            SkillsError::Cancelled => "cancelled",
+            SkillsError::TimedOut { .. } => "timed_out",
+            SkillsError::Other(_) => "other",
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            SkillsError::SkillNotFound { path, .. } | SkillsError::Io { path, .. } => {
+                path.as_deref()
+            }
+            SkillsError::NotFound { path }
+            | SkillsError::PermissionDenied { path, .. }
+            | SkillsError::InvalidConfig { path, .. }
+            | SkillsError::Git { path, .. }
+            | SkillsError::InvalidSkillConfig { path, .. }
+            | SkillsError::HasDependents { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    fn agent(&self) -> Option<&str> {
+        match self {
+            SkillsError::UnknownAgent { agent } => Some(agent),
+            _ => None,
+        }
+    }
+
+    fn field_errors(&self) -> &[FieldError] {
+        match self {
+            SkillsError::InvalidSkillConfig { errors, .. } => errors,
+            _ => &[],
+        }
+    }
+
+    fn dependents(&self) -> &[PathBuf] {
+        match self {
+            SkillsError::HasDependents { dependents, .. } => dependents,
+            _ => &[],
+        }
+    }
+
+    fn skill(&self) -> Option<&str> {
+        match self {
+            SkillsError::SkillNotFound { skill, .. }
+            | SkillsError::IntegrityMismatch { skill, .. } => Some(skill),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SkillsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkillsError::UnknownAgent { agent } => {
+                write!(f, "Unknown or disabled agent: {}", agent)
+            }
+            SkillsError::SkillNotFound {
+                skill,
+                path: Some(path),
+            } => {
+                write!(f, "Skill '{}' not found in {}", skill, path.display())
+            }
+            SkillsError::SkillNotFound { skill, path: None } => {
+                write!(f, "Skill '{}' not found", skill)
+            }
+            SkillsError::NotFound { path } => write!(f, "{} does not exist", path.display()),
+            SkillsError::PermissionDenied { path, message } => {
+                write!(f, "Permission denied for {}: {}", path.display(), message)
+            }
+            SkillsError::Io {
+                path: Some(path),
+                message,
+            } => {
+                write!(f, "{}: {}", path.display(), message)
+            }
+            SkillsError::Io {
+                path: None,
+                message,
+            } => write!(f, "{}", message),
+            SkillsError::InvalidConfig { path, message } => {
+                write!(f, "Invalid {}: {}", path.display(), message)
+            }
+            SkillsError::InvalidInput(message) | SkillsError::Other(message) => {
+                write!(f, "{}", message)
+            }
+            SkillsError::Git {
+                path,
+                action,
+                message,
+            } => write!(
+                f,
+                "git {} failed in {}: {}",
+                action,
+                path.display(),
+                message
+            ),
+            SkillsError::CommandNotFound { command } => {
+                write!(f, "{} is not installed or not on PATH", command)
+            }
+            SkillsError::CommandFailed { command, message } => {
+                write!(f, "{} failed: {}", command, message)
+            }
+            SkillsError::Network { url, message } => {
+                write!(f, "Failed to download {}: {}", url, message)
+            }
+            SkillsError::IntegrityMismatch {
+                skill,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Skill '{}' doesn't match its recorded digest (expected {}, got {})",
+                skill, expected, actual
+            ),
+            SkillsError::InvalidSkillConfig { path, errors } => write!(
+                f,
+                "Invalid {}: {}",
+                path.display(),
+                errors
+                    .iter()
+                    .map(|e| match e.pointer.as_str() {
+                        "" => e.message.clone(),
+                        pointer => format!("{}: {}", pointer, e.message),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+            SkillsError::VaultLocked { env } => {
+                write!(f, "The secrets vault is locked; unlock it or set {}", env)
+            }
+            SkillsError::HasDependents { path, dependents } => write!(
+                f,
+                "{} is still linked from {}",
+                path.display(),
+                dependents
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            SkillsError::Cancelled => write!(f, "Operation cancelled"),
+            SkillsError::TimedOut { seconds } => {
+                write!(f, "Operation timed out after {}s", seconds)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SkillsError {}
+
+impl serde::Serialize for SkillsError {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct Context<'a> {
+            path: Option<&'a Path>,
+            agent: Option<&'a str>,
+            skill: Option<&'a str>,
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            errors: &'a [FieldError],
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            dependents: &'a [PathBuf],
+        }
+
+        let mut state = serializer.serialize_struct("SkillsError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field(
+            "context",
+            &Context {
+                path: self.path(),
+                agent: self.agent(),
+                skill: self.skill(),
+                errors: self.field_errors(),
+                dependents: self.dependents(),
+            },
+        )?;
+        state.end()
+    }
+}
+
+impl From<Interrupted> for SkillsError {
+    fn from(reason: Interrupted) -> Self {
+        match reason {
+            Interrupted::Cancelled => SkillsError::Cancelled,
+            Interrupted::TimedOut(limit) => SkillsError::TimedOut {
+                seconds: limit.as_secs(),
+            },
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! Git through an embedded libgit2, so skills can be cloned, inspected and
+//! updated without a `git` binary on PATH.
+//!
+//! Long-running calls take an [`Operation`] and stop at the next transfer
+//! callback when it is cancelled or times out.
+
+use std::cell::Cell;
+use std::fs;
+use std::path::Path;
+
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{
+    AutotagOption, Cred, CredentialType, ErrorClass, FetchOptions, Oid, RemoteCallbacks, Repository,
+};
+
+use crate::error::{Result, SkillsError};
+use crate::operations::Operation;
+
+const REMOTE: &str = "origin";
+
+/// The commit checked out in a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Head {
+    pub commit: String,
+    /// Committer date as `YYYY-MM-DD`, in the committer's time zone
+    pub date: Option<String>,
+}
+
+impl Head {
+    /// Abbreviated hash, like `git rev-parse --short`.
+    pub fn short(&self) -> String {
+        self.commit.chars().take(7).collect()
+    }
+}
+
+fn open(dir: &Path) -> Result<Repository> {
+    Repository::open(dir).map_err(|e| error(dir, "open", e))
+}
+
+/// Maps a libgit2 failure on the repository at `dir`.
+fn error(dir: &Path, action: &str, err: git2::Error) -> SkillsError {
+    match err.class() {
+        ErrorClass::Net | ErrorClass::Http | ErrorClass::Ssh | ErrorClass::Ssl => {
+            SkillsError::Network {
+                url: dir.display().to_string(),
+                message: err.message().to_string(),
+            }
+        }
+        _ => SkillsError::Git {
+            path: dir.to_path_buf(),
+            action: action.to_string(),
+            message: err.message().to_string(),
+        },
+    }
+}
+
+/// Like [`error`], but reports the remote URL for network failures and
+/// the operation's own error when a callback aborted the transfer.
+fn transfer_error(
+    dir: &Path,
+    url: &str,
+    action: &str,
+    err: git2::Error,
+    op: &Operation,
+) -> SkillsError {
+    if let Some(reason) = op.interrupted() {
+        return reason.into();
+    }
+    match error(dir, action, err) {
+        SkillsError::Network { message, .. } => SkillsError::Network {
+            url: url.to_string(),
+            message,
+        },
+        other => other,
+    }
+}
+
+pub fn head(dir: &Path) -> Result<Head> {
+    let repo = open(dir)?;
+    let commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| error(dir, "read HEAD", e))?;
+    let time = commit.committer().when();
+    let date = chrono::FixedOffset::east_opt(time.offset_minutes() * 60)
+        .zip(chrono::DateTime::from_timestamp(time.seconds(), 0))
+        .map(|(offset, at)| at.with_timezone(&offset).format("%Y-%m-%d").to_string());
+    Ok(Head {
+        commit: commit.id().to_string(),
+        date,
+    })
+}
+
+/// A cheap stand-in for HEAD that changes whenever HEAD moves, read from
+/// `.git` without opening the repository. `None` unless `dir` is a
+/// working tree with a `.git` directory.
+pub fn head_fingerprint(dir: &Path) -> Option<String> {
+    let git_dir = dir.join(".git");
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let Some(reference) = head.trim().strip_prefix("ref: ") else {
+        return Some(head.trim().to_string());
+    };
+    // A loose ref, or one that was packed by `git gc`
+    match fs::read_to_string(git_dir.join(reference)) {
+        Ok(commit) => Some(commit.trim().to_string()),
+        Err(_) => {
+            let packed = fs::metadata(git_dir.join("packed-refs"))
+                .and_then(|m| m.modified())
+                .ok()?;
+            Some(format!("{} {:?}", reference, packed))
+        }
+    }
+}
+
+/// URL of `origin`, if the repository has one.
+pub fn remote_url(dir: &Path) -> Result<Option<String>> {
+    let repo = open(dir)?;
+    let url = match repo.find_remote(REMOTE) {
+        Ok(remote) => remote.url().map(str::to_string),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => None,
+        Err(e) => return Err(error(dir, "read remote", e)),
+    };
+    Ok(url)
+}
+
+/// Callbacks that pick up credentials the way the git CLI would and abort
+/// the transfer once `op` is interrupted.
+fn callbacks(op: &Operation) -> RemoteCallbacks<'_> {
+    let mut callbacks = RemoteCallbacks::new();
+    // libgit2 keeps asking while credentials are rejected; give each kind one try
+    let tried = Cell::new(CredentialType::empty());
+    callbacks.credentials(move |url, username, allowed| {
+        let available = allowed - tried.get();
+        if available.contains(CredentialType::SSH_KEY) {
+            tried.set(tried.get() | CredentialType::SSH_KEY);
+            Cred::ssh_key_from_agent(username.unwrap_or("git"))
+        } else if available.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            tried.set(tried.get() | CredentialType::USER_PASS_PLAINTEXT);
+            let config = git2::Config::open_default()?;
+            Cred::credential_helper(&config, url, username)
+        } else if available.contains(CredentialType::DEFAULT) {
+            tried.set(tried.get() | CredentialType::DEFAULT);
+            Cred::default()
+        } else {
+            Err(git2::Error::from_str("no usable credentials"))
+        }
+    });
+    callbacks.transfer_progress(|_| op.interrupted().is_none());
+    callbacks.sideband_progress(|_| op.interrupted().is_none());
+    callbacks
+}
+
+/// libgit2 only calls back while data arrives, so a stalled connection
+/// would never notice the deadline. Its socket timeouts bound the stall
+/// instead; they are process-wide, so each transfer sets them to the time
+/// its own operation has left (0 is libgit2's default of no limit).
+fn limit_stalls(op: &Operation) {
+    let ms = op.remaining().map_or(0, |left| {
+        i32::try_from(left.as_millis().max(1)).unwrap_or(i32::MAX)
+    });
+    // SAFETY: these only set libgit2 globals, which it reads when a
+    // connection is opened or waits for data
+    unsafe {
+        let _ = git2::opts::set_server_connect_timeout_in_milliseconds(ms);
+        let _ = git2::opts::set_server_timeout_in_milliseconds(ms);
+    }
+}
+
+fn fetch_options(op: &Operation, shallow: bool) -> FetchOptions<'_> {
+    limit_stalls(op);
+    let mut options = FetchOptions::new();
+    options
+        .remote_callbacks(callbacks(op))
+        .download_tags(AutotagOption::Auto);
+    if shallow {
+        options.depth(1);
+    }
+    options
+}
+
+/// Local repositories are fetched through libgit2's local transport, which
+/// can't do shallow fetches.
+fn is_remote(url: &str) -> bool {
+    (url.contains("://") && !url.starts_with("file://")) || url.contains('@')
+}
+
+/// Whether `reference` looks like a (possibly abbreviated) commit hash,
+/// which has to be found in history rather than fetched by name.
+fn is_commit(reference: &str) -> bool {
+    (7..=40).contains(&reference.len()) && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Clones `url` into `dest` with `reference` (a branch, tag or commit)
+/// checked out, or the default branch when there is none.
+pub fn clone(url: &str, reference: Option<&str>, dest: &Path, op: &Operation) -> Result<()> {
+    op.log(format!("Cloning {} (ref={:?})", url, reference));
+    let shallow = is_remote(url);
+    let Some(reference) = reference else {
+        RepoBuilder::new()
+            .fetch_options(fetch_options(op, shallow))
+            .clone(url, dest)
+            .map_err(|e| transfer_error(dest, url, "clone", e, op))?;
+        return Ok(());
+    };
+
+    let repo = Repository::init(dest).map_err(|e| error(dest, "init", e))?;
+    let mut remote = repo
+        .remote(REMOTE, url)
+        .map_err(|e| error(dest, "add remote", e))?;
+    // A commit may be anywhere in history, so it needs every branch in full
+    let (refspecs, shallow) = if is_commit(reference) {
+        let refspecs = vec![
+            "+refs/heads/*:refs/remotes/origin/*".to_string(),
+            "+refs/tags/*:refs/tags/*".to_string(),
+        ];
+        (refspecs, false)
+    } else {
+        let refspecs = vec![
+            format!("+refs/heads/{0}:refs/remotes/origin/{0}", reference),
+            format!("+refs/tags/{0}:refs/tags/{0}", reference),
+        ];
+        (refspecs, shallow)
+    };
+    remote
+        .fetch(&refspecs, Some(&mut fetch_options(op, shallow)), None)
+        .map_err(|e| transfer_error(dest, url, "fetch", e, op))?;
+
+    let target = if is_commit(reference) {
+        repo.revparse_single(reference)
+    } else {
+        repo.revparse_single(&format!("refs/remotes/origin/{}", reference))
+            .or_else(|_| repo.revparse_single(&format!("refs/tags/{}", reference)))
+    };
+    let commit = target
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| SkillsError::Git {
+            path: dest.to_path_buf(),
+            action: "checkout".to_string(),
+            message: format!("{} has no branch, tag or commit named '{}'", url, reference),
+        })?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+        .and_then(|_| repo.set_head_detached(commit.id()))
+        .map_err(|e| error(dest, "checkout", e))
+}
+
+/// Fetches `origin` with its configured refspecs.
+pub fn fetch(dir: &Path, op: &Operation) -> Result<()> {
+    let repo = open(dir)?;
+    let mut remote = repo
+        .find_remote(REMOTE)
+        .map_err(|e| error(dir, "find remote", e))?;
+    let url = remote.url().unwrap_or(REMOTE).to_string();
+    // Keep a shallow clone shallow
+    let shallow = repo.is_shallow() && is_remote(&url);
+    remote
+        .fetch::<&str>(&[], Some(&mut fetch_options(op, shallow)), None)
+        .map_err(|e| transfer_error(dir, &url, "fetch", e, op))
+}
+
+/// The commit the checked-out branch tracks: its upstream, else
+/// `origin/HEAD` or `origin/main`. `None` when there is nothing to track.
+pub fn upstream_commit(dir: &Path) -> Result<Option<String>> {
+    let repo = open(dir)?;
+    Ok(upstream(&repo).map(|oid| oid.to_string()))
+}
+
+fn upstream(repo: &Repository) -> Option<Oid> {
+    let tracked = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string))
+        .and_then(|name| repo.find_branch(&name, git2::BranchType::Local).ok())
+        .and_then(|branch| branch.upstream().ok())
+        .and_then(|upstream| upstream.get().target());
+    tracked.or_else(|| {
+        ["refs/remotes/origin/HEAD", "refs/remotes/origin/main"]
+            .iter()
+            .find_map(|name| repo.refname_to_id(name).ok())
+    })
+}
+
+/// Fetches and fast-forwards the checked-out branch to its upstream,
+/// like `git pull --ff-only`. Returns a one-line summary.
+pub fn fast_forward(dir: &Path, op: &Operation) -> Result<String> {
+    fetch(dir, op)?;
+    let repo = open(dir)?;
+    let fail = |message: &str| SkillsError::Git {
+        path: dir.to_path_buf(),
+        action: "fast-forward".to_string(),
+        message: message.to_string(),
+    };
+
+    let mut head = repo.head().map_err(|e| error(dir, "read HEAD", e))?;
+    if !head.is_branch() {
+        return Err(fail("HEAD is detached, so there is no branch to update"));
+    }
+    let target = upstream(&repo).ok_or_else(|| fail("the branch has no upstream"))?;
+    let annotated = repo
+        .find_annotated_commit(target)
+        .map_err(|e| error(dir, "fast-forward", e))?;
+    let (analysis, _) = repo
+        .merge_analysis(&[&annotated])
+        .map_err(|e| error(dir, "fast-forward", e))?;
+
+    if analysis.is_up_to_date() {
+        return Ok("Already up to date.".to_string());
+    }
+    if !analysis.is_fast_forward() {
+        return Err(fail("local commits have diverged from the upstream"));
+    }
+
+    let old = head.target().map(|oid| oid.to_string()).unwrap_or_default();
+    let commit = repo
+        .find_commit(target)
+        .map_err(|e| error(dir, "fast-forward", e))?;
+    // A safe checkout refuses to overwrite local edits, like git would
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| error(dir, "checkout", e))?;
+    head.set_target(target, "fast-forward")
+        .map_err(|e| error(dir, "fast-forward", e))?;
+    let new = target.to_string();
+    Ok(format!(
+        "Updating {}..{}\nFast-forward",
+        &old[..old.len().min(7)],
+        &new[..7]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn stalled_clone_times_out() {
+        // Accepts connections and never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/skills.git", listener.local_addr().unwrap());
+        let dest = tempfile::tempdir().unwrap();
+        let op = crate::test_support::operation().with_timeout(Some(Duration::from_secs(1)));
+
+        let started = Instant::now();
+        let result = clone(&url, None, &dest.path().join("repo"), &op);
+        assert!(
+            matches!(result, Err(SkillsError::TimedOut { .. })),
+            "{result:?}"
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+        drop(listener);
+    }
+}
@@ -0,0 +1,246 @@
+//! Switching an installed skill between a symlink into the global store
+//! and a standalone copy.
+//!
+//! Either way the agent's entry keeps its path, so its provenance entry
+//! stays valid. A copy that gets replaced by a link goes to the trash
+//! rather than being deleted, since it may hold a `.git` directory or other
+//! files that aren't part of its digest.
+
+use std::fs;
+use std::path::Path;
+
+use crate::agents::{AgentRegistry, GLOBAL_AGENT};
+use crate::digest;
+use crate::error::{Result, SkillsError};
+use crate::links;
+use crate::operations::Operation;
+use crate::paths;
+use crate::scanner;
+use crate::scope::SkillScope;
+use crate::source_manager::{self, SourceIndex};
+use crate::trash;
+
+use super::{agent_dir, place, same_dir, InstallMode};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Converted {
+    pub name: String,
+    pub agent: String,
+    pub path: String,
+    pub mode: InstallMode,
+    /// Store copy the skill now links to
+    pub linked_to: Option<String>,
+    /// Trash entry holding the copy a link replaced
+    pub trash_id: Option<String>,
+    /// False when the skill was already installed that way
+    pub changed: bool,
+}
+
+/// Converts `name` for `agent` (in `project`, else the home directory) to
+/// `mode`.
+pub fn convert(
+    name: &str,
+    agent: &str,
+    mode: InstallMode,
+    project: Option<&Path>,
+    registry: &AgentRegistry,
+    op: &Operation,
+) -> Result<Converted> {
+    if agent == GLOBAL_AGENT {
+        return Err(SkillsError::InvalidInput(
+            "The global store always holds real copies".to_string(),
+        ));
+    }
+    let home = paths::home_dir()?;
+    let path = agent_dir(registry.require(agent)?, &home, project).join(name);
+    if fs::symlink_metadata(&path).is_err() {
+        return Err(SkillsError::skill_not_found(name, Some(&path)));
+    }
+    let store_dir = agent_dir(registry.require(GLOBAL_AGENT)?, &home, project);
+    // Linking the store copy to itself would trash the only real copy
+    if path.parent().is_some_and(|dir| same_dir(dir, &store_dir)) {
+        return Err(SkillsError::InvalidInput(format!(
+            "{} keeps its skills in the global store at {}, which always holds real copies",
+            agent,
+            store_dir.display()
+        )));
+    }
+    let (scope, store_scope) = match project {
+        Some(root) => (SkillScope::project(root), SkillScope::project(root)),
+        None => (SkillScope::for_home_agent(agent), SkillScope::Global),
+    };
+
+    let mut converted = Converted {
+        name: name.to_string(),
+        agent: agent.to_string(),
+        path: path.to_string_lossy().to_string(),
+        mode,
+        linked_to: None,
+        trash_id: None,
+        changed: false,
+    };
+    let is_link = links::is_link(&path);
+    match mode {
+        InstallMode::Copy if !is_link => {}
+        InstallMode::Copy => {
+            let target = fs::canonicalize(&path).map_err(|_| {
+                SkillsError::InvalidInput(format!(
+                    "{} is a broken link; repair it with doctor first",
+                    path.display()
+                ))
+            })?;
+            // Copy next to the link first so a failure leaves the link alone
+            let staging = path.with_file_name(format!(".{}.converting", name));
+            op.begin_write(&staging);
+            place::copy_dir(&target, &staging, agent, &scope)?.report_skipped(op);
+            place::remove_entry(&path).map_err(|e| SkillsError::io(&path, e))?;
+            fs::rename(&staging, &path).map_err(|e| SkillsError::io(&path, e))?;
+            op.complete_write(&staging);
+            op.log(format!("Replaced link {} with a copy", path.display()));
+
+            // A link installed by hand has no entry of its own; inherit the
+            // one of the copy it pointed to. Store entries are keyed by the
+            // store path as installed, which may differ from the resolved one
+            let index = SourceIndex::load()?;
+            if index.entry(&scope, agent, &path).is_none() {
+                let store_copy = store_dir.join(target.file_name().unwrap_or_default());
+                let inherited = [store_copy.as_path(), target.as_path()]
+                    .into_iter()
+                    .find_map(|p| index.entry(&store_scope, GLOBAL_AGENT, p));
+                if let Some(entry) = inherited {
+                    let mut entry = entry.clone();
+                    entry.scope = Some(scope.clone());
+                    entry.agent = Some(agent.to_string());
+                    entry.path = Some(converted.path.clone());
+                    source_manager::restore_entry(entry)?;
+                }
+            }
+            converted.changed = true;
+        }
+        InstallMode::Symlink if is_link => {
+            converted.linked_to = links::resolve(&path).map(|t| t.to_string_lossy().to_string());
+        }
+        InstallMode::Symlink => {
+            let store_copy = store_dir.join(name);
+            let created = if store_copy.is_dir() {
+                let ours = digest::dir_digest(&path)?;
+                let theirs = digest::dir_digest(&store_copy)?;
+                if ours != theirs {
+                    return Err(SkillsError::InvalidInput(format!(
+                        "{} differs from the global copy at {} ({} vs {}); remove one of them first",
+                        path.display(),
+                        store_copy.display(),
+                        ours,
+                        theirs
+                    )));
+                }
+                false
+            } else {
+                place::copy_dir(&path, &store_copy, GLOBAL_AGENT, &store_scope)?.report_skipped(op);
+                true
+            };
+
+            let trashed = trash::move_to_trash(&path, agent, &scope)?;
+            if let Err(e) = place::link_dir(&store_copy, &path) {
+                // Put the copy back as it was
+                let _ = trash::restore(&trashed.id);
+                if created {
+                    let _ = place::remove_entry(&store_copy);
+                }
+                return Err(SkillsError::io(&path, e));
+            }
+            op.log(format!(
+                "Linked {} to {}",
+                path.display(),
+                store_copy.display()
+            ));
+
+            if let Some(entry) = trashed.provenance {
+                if created {
+                    let mut store_entry = entry.clone();
+                    store_entry.scope = Some(store_scope);
+                    store_entry.agent = Some(GLOBAL_AGENT.to_string());
+                    store_entry.path = Some(store_copy.to_string_lossy().to_string());
+                    source_manager::restore_entry(store_entry)?;
+                }
+                source_manager::restore_entry(entry)?;
+            }
+            converted.linked_to = Some(store_copy.to_string_lossy().to_string());
+            converted.trash_id = Some(trashed.id);
+            converted.changed = true;
+        }
+    }
+    scanner::invalidate(&path);
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{operation, registry, sandbox, write_skill};
+
+    #[test]
+    fn refuses_agents_that_use_the_store() {
+        sandbox();
+        let project = tempfile::tempdir().unwrap();
+        let store_copy = project.path().join(".agents/skills/pdf");
+        write_skill(&store_copy, "pdf");
+
+        for agent in ["codex", "amp", "opencode"] {
+            let result = convert(
+                "pdf",
+                agent,
+                InstallMode::Symlink,
+                Some(project.path()),
+                &registry(),
+                &operation(),
+            );
+            assert!(
+                matches!(result, Err(SkillsError::InvalidInput(_))),
+                "{agent}: {result:?}"
+            );
+        }
+        assert!(!links::is_link(&store_copy));
+        assert!(store_copy.join("SKILL.md").is_file());
+    }
+
+    #[test]
+    fn copy_to_link_and_back() {
+        sandbox();
+        let project = tempfile::tempdir().unwrap();
+        let copy = project.path().join(".claude/skills/docx");
+        write_skill(&copy, "docx");
+        let (registry, op) = (registry(), operation());
+
+        let linked = convert(
+            "docx",
+            "claude-code",
+            InstallMode::Symlink,
+            Some(project.path()),
+            &registry,
+            &op,
+        )
+        .unwrap();
+        let store_copy = project.path().join(".agents/skills/docx");
+        assert!(linked.changed && linked.trash_id.is_some());
+        assert!(links::is_link(&copy));
+        assert_eq!(
+            fs::canonicalize(&copy).unwrap(),
+            fs::canonicalize(&store_copy).unwrap()
+        );
+
+        let copied = convert(
+            "docx",
+            "claude-code",
+            InstallMode::Copy,
+            Some(project.path()),
+            &registry,
+            &op,
+        )
+        .unwrap();
+        assert!(copied.changed);
+        assert!(!links::is_link(&copy));
+        assert!(copy.join("SKILL.md").is_file());
+        assert!(store_copy.join("SKILL.md").is_file());
+    }
+}
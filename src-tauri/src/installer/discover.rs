@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::manifest::{SkillManifest, SKILL_FILE};
+
+// Deep enough for layouts like `<repo>/skills/<category>/<skill>/SKILL.md`
+const MAX_DEPTH: usize = 6;
+const IGNORED_DIRS: &[&str] = &[".git", "node_modules"];
+
+#[derive(Debug, Clone)]
+pub struct DiscoveredSkill {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+/// A SKILL.md that was found but can't be installed.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SkippedSkill {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Turns a skill name into a safe directory name.
+pub fn sanitize_name(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    sanitized.trim_matches(|c| c == '-' || c == '.').to_string()
+}
+
+/// Finds every directory under `root` that contains a SKILL.md.
+pub fn discover_skills(root: &Path) -> (Vec<DiscoveredSkill>, Vec<SkippedSkill>) {
+    let mut skills = Vec::new();
+    let mut skipped = Vec::new();
+    let mut seen = HashSet::new();
+
+    let walker = WalkDir::new(root)
+        .max_depth(MAX_DEPTH)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            !(e.file_type().is_dir()
+                && IGNORED_DIRS.contains(&e.file_name().to_string_lossy().as_ref()))
+        });
+
+    for entry in walker.flatten() {
+        if !entry.file_type().is_file() || entry.file_name() != SKILL_FILE {
+            continue;
+        }
+        let dir = match entry.path().parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => continue,
+        };
+
+        let manifest = match SkillManifest::load(&dir) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                skipped.push(SkippedSkill {
+                    path: dir.to_string_lossy().to_string(),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        let dir_name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let name = sanitize_name(manifest.name.as_deref().unwrap_or(&dir_name));
+        if name.is_empty() {
+            skipped.push(SkippedSkill {
+                path: dir.to_string_lossy().to_string(),
+                reason: "Skill has no usable name".to_string(),
+            });
+            continue;
+        }
+        if !seen.insert(name.clone()) {
+            skipped.push(SkippedSkill {
+                path: dir.to_string_lossy().to_string(),
+                reason: format!("Another skill named '{}' was already found", name),
+            });
+            continue;
+        }
+
+        skills.push(DiscoveredSkill { name, dir });
+    }
+
+    (skills, skipped)
+}
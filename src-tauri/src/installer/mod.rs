@@ -0,0 +1,728 @@
+//! In-process skill installer.
+//!
+//! Resolves a source, discovers the SKILL.md directories inside it and places
+//! them into agent directories, either as copies or as symlinks into the
+//! shared `.agents/skills` store. The `npx skills` CLI is kept as a fallback
+//! backend.
+
+pub mod convert;
+pub mod discover;
+mod npx;
+pub mod place;
+pub mod source;
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agents::{AgentDefinition, AgentRegistry, GLOBAL_AGENT};
+use crate::digest;
+use crate::error::{Result, SkillsError};
+use crate::links;
+use crate::lockfile::{Lockfile, LOCK_FILE};
+use crate::manifest::SKILL_FILE;
+use crate::operations::{Operation, Phase};
+use crate::paths;
+use crate::scope::SkillScope;
+use crate::trash;
+use discover::SkippedSkill;
+use source::SkillSource;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallMode {
+    Symlink,
+    Copy,
+}
+
+impl InstallMode {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "symlink" => Ok(InstallMode::Symlink),
+            "copy" => Ok(InstallMode::Copy),
+            other => Err(SkillsError::InvalidInput(format!(
+                "Unknown install mode: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Native,
+    Npx,
+}
+
+impl Backend {
+    pub fn parse(value: Option<&str>) -> Result<Self> {
+        match value {
+            None | Some("native") => Ok(Backend::Native),
+            Some("npx") => Ok(Backend::Npx),
+            Some(other) => Err(SkillsError::InvalidInput(format!(
+                "Unknown install backend: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstallRequest {
+    pub source: String,
+    /// Branch, tag or commit to check out, overriding any in the source URL
+    pub reference: Option<String>,
+    /// Only install the skill with this name
+    pub skill: Option<String>,
+    /// Also install into the shared `.agents/skills` store
+    pub global: bool,
+    pub agents: Vec<String>,
+    pub mode: InstallMode,
+    /// Install into this project instead of the home directory
+    pub project: Option<PathBuf>,
+    /// Only used by the npx backend
+    pub auto_confirm: bool,
+    /// Install the commits in skills.lock and refuse skills whose content
+    /// doesn't match it
+    pub frozen: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoveRequest {
+    pub skill_ids: Vec<String>,
+    pub global: bool,
+    pub agents: Vec<String>,
+    /// Remove every skill from the target agents
+    pub remove_all: bool,
+    pub project: Option<PathBuf>,
+    /// Only used by the npx backend
+    pub auto_confirm: bool,
+    /// Also remove links that point to a removed copy; without it, removing
+    /// a copy that is still linked fails
+    pub cascade: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InstalledSkill {
+    pub name: String,
+    pub agent: String,
+    pub path: Option<String>,
+    pub mode: InstallMode,
+    /// Canonical copy a symlinked install points to
+    pub linked_to: Option<String>,
+    /// Digest of the skill as it was in the source
+    pub digest: Option<String>,
+    /// Directory of the skill inside the source
+    pub subpath: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InstallReport {
+    pub source: String,
+    /// Branch or tag that was requested, for git sources
+    pub reference: Option<String>,
+    /// Commit that was installed, for git sources
+    pub commit: Option<String>,
+    pub installed: Vec<InstalledSkill>,
+    pub skipped: Vec<SkippedSkill>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RemovedSkill {
+    pub name: String,
+    pub agent: String,
+    pub path: Option<String>,
+    /// Trash entry to restore it from
+    pub trash_id: Option<String>,
+    /// Set when only a link was removed: the copy it pointed to
+    pub link_target: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RemoveReport {
+    pub removed: Vec<RemovedSkill>,
+    pub missing: Vec<RemovedSkill>,
+}
+
+/// Skills directory of `agent`, in `project` when given, otherwise under `home`.
+fn agent_dir(agent: &AgentDefinition, home: &Path, project: Option<&Path>) -> PathBuf {
+    match project {
+        Some(root) => agent.project_skills_dir(root),
+        None => agent.skills_dir(home),
+    }
+}
+
+/// Whether `a` and `b` are the same directory, following links. Several
+/// agents keep their project skills in the store's `.agents/skills`.
+pub(crate) fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (fs::canonicalize(a), fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+/// Fails when `agent`'s skills directory is really the global store, where
+/// removing one of its skills would take it from every agent sharing the
+/// store.
+pub(crate) fn refuse_store_dir(agent: &str, dir: &Path, store_dir: &Path) -> Result<()> {
+    if agent != GLOBAL_AGENT && same_dir(dir, store_dir) {
+        return Err(SkillsError::InvalidInput(format!(
+            "{} keeps its skills in the global store at {}; remove them from the global agent",
+            agent,
+            store_dir.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Resolves the requested agents to directories, dropping duplicates.
+fn target_dirs<'a>(
+    registry: &'a AgentRegistry,
+    agents: &[String],
+    global: bool,
+    home: &Path,
+    project: Option<&Path>,
+) -> Result<Vec<(&'a AgentDefinition, PathBuf)>> {
+    let mut ids: Vec<&str> = agents.iter().map(String::as_str).collect();
+    if global && !ids.contains(&GLOBAL_AGENT) {
+        ids.insert(0, GLOBAL_AGENT);
+    }
+
+    let mut seen = HashSet::new();
+    let mut targets = Vec::new();
+    for id in ids {
+        let agent = registry.require(id)?;
+        let dir = agent_dir(agent, home, project);
+        if seen.insert(dir.clone()) {
+            targets.push((agent, dir));
+        }
+    }
+    Ok(targets)
+}
+
+pub fn install(
+    request: &InstallRequest,
+    backend: Backend,
+    registry: &AgentRegistry,
+    op: &Operation,
+) -> Result<InstallReport> {
+    match backend {
+        Backend::Npx => {
+            if request.reference.is_some() || request.frozen {
+                return Err(SkillsError::InvalidInput(
+                    "Pinned refs and frozen installs need the native installer".to_string(),
+                ));
+            }
+            op.set_phase(Phase::Installing, 10);
+            let mut report = npx::install(request, op)?;
+            // The CLI doesn't print paths; point at where it puts skills
+            let home = paths::home_dir()?;
+            for skill in &mut report.installed {
+                let dir = agent_dir(
+                    registry.require(&skill.agent)?,
+                    &home,
+                    request.project.as_deref(),
+                )
+                .join(&skill.name);
+                if dir.exists() {
+                    skill.path = Some(dir.to_string_lossy().to_string());
+                }
+            }
+            Ok(report)
+        }
+        Backend::Native => install_native(request, registry, op),
+    }
+}
+
+fn install_native(
+    request: &InstallRequest,
+    registry: &AgentRegistry,
+    op: &Operation,
+) -> Result<InstallReport> {
+    let home = paths::home_dir()?;
+    let project = request.project.as_deref();
+
+    let targets = target_dirs(registry, &request.agents, request.global, &home, project)?;
+    if targets.is_empty() {
+        return Err(SkillsError::InvalidInput(
+            "No target agents selected".to_string(),
+        ));
+    }
+
+    op.set_phase(Phase::Resolving, 5);
+    let lock = if request.frozen {
+        let lock = Lockfile::load(project)?;
+        if !lock.has_source(&request.source) {
+            return Err(SkillsError::InvalidInput(format!(
+                "{} is not in {}",
+                request.source, LOCK_FILE
+            )));
+        }
+        Some(lock)
+    } else {
+        None
+    };
+    let reference = match &lock {
+        Some(lock) => lock
+            .commit_for(&request.source)
+            .or(request.reference.as_deref()),
+        None => request.reference.as_deref(),
+    };
+    let source = SkillSource::parse(&request.source)?.pinned(reference)?;
+    op.log(format!("Resolved source: {:?}", source));
+
+    op.set_phase(Phase::Fetching, 10);
+    let fetched = source.fetch(op)?;
+
+    let (mut skills, mut skipped) = discover::discover_skills(fetched.root());
+    if let Some(ref wanted) = request.skill {
+        let wanted = discover::sanitize_name(wanted);
+        skills.retain(|s| s.name == wanted || s.dir.ends_with(&wanted));
+        if skills.is_empty() {
+            return Err(SkillsError::SkillNotFound {
+                skill: wanted,
+                path: Some(fetched.root().to_path_buf()),
+            });
+        }
+    }
+    if skills.is_empty() {
+        return Err(SkillsError::InvalidInput(format!(
+            "No {} found in {}",
+            SKILL_FILE, request.source
+        )));
+    }
+
+    let mut digests = Vec::new();
+    for skill in &skills {
+        let digest = digest::dir_digest(&skill.dir)?;
+        if let Some(lock) = &lock {
+            let locked = lock.find(&request.source, &skill.name).ok_or_else(|| {
+                SkillsError::InvalidInput(format!(
+                    "{} from {} is not in {}",
+                    skill.name, request.source, LOCK_FILE
+                ))
+            })?;
+            if locked.digest != digest {
+                return Err(SkillsError::IntegrityMismatch {
+                    skill: skill.name.clone(),
+                    expected: locked.digest.clone(),
+                    actual: digest,
+                });
+            }
+        }
+        let subpath = skill
+            .dir
+            .strip_prefix(fetched.root())
+            .ok()
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .filter(|p| !p.is_empty());
+        digests.push((digest, subpath));
+    }
+
+    let store_dir = agent_dir(registry.require(GLOBAL_AGENT)?, &home, project);
+    let scope_of = |agent: &str| match project {
+        Some(root) => SkillScope::project(root),
+        None => SkillScope::for_home_agent(agent),
+    };
+    let mut installed = Vec::new();
+
+    op.set_phase(Phase::Installing, 40);
+    for (index, (skill, (digest, subpath))) in skills.iter().zip(&digests).enumerate() {
+        let digest = Some(digest.clone());
+        match request.mode {
+            InstallMode::Copy => {
+                for (agent, dir) in &targets {
+                    let dest = dir.join(&skill.name);
+                    op.check()?;
+                    let copied =
+                        place::copy_dir(&skill.dir, &dest, &agent.id, &scope_of(&agent.id))?;
+                    skipped.extend(copied.report_skipped(op));
+                    installed.push(InstalledSkill {
+                        name: skill.name.clone(),
+                        agent: agent.id.clone(),
+                        path: Some(dest.to_string_lossy().to_string()),
+                        mode: InstallMode::Copy,
+                        linked_to: None,
+                        digest: digest.clone(),
+                        subpath: subpath.clone(),
+                    });
+                }
+            }
+            InstallMode::Symlink => {
+                // One canonical copy in the store, every agent links to it
+                let canonical = store_dir.join(&skill.name);
+                op.check()?;
+                let copied = place::copy_dir(
+                    &skill.dir,
+                    &canonical,
+                    GLOBAL_AGENT,
+                    &scope_of(GLOBAL_AGENT),
+                )?;
+                skipped.extend(copied.report_skipped(op));
+
+                for (agent, dir) in &targets {
+                    let dest = dir.join(&skill.name);
+                    if dest == canonical {
+                        installed.push(InstalledSkill {
+                            name: skill.name.clone(),
+                            agent: agent.id.clone(),
+                            path: Some(dest.to_string_lossy().to_string()),
+                            mode: InstallMode::Copy,
+                            linked_to: None,
+                            digest: digest.clone(),
+                            subpath: subpath.clone(),
+                        });
+                        continue;
+                    }
+
+                    op.check()?;
+                    // A copy being replaced by a link may hold local edits
+                    if dest.is_dir() && !links::is_link(&dest) {
+                        trash::move_to_trash(&dest, &agent.id, &scope_of(&agent.id))?;
+                    }
+                    op.begin_write(&dest);
+                    let mode = match place::link_dir(&canonical, &dest) {
+                        Ok(()) => InstallMode::Symlink,
+                        Err(e) => {
+                            // Symlinks can need extra privileges on Windows
+                            op.log(format!(
+                                "Symlink to {} failed ({}), copying instead",
+                                dest.display(),
+                                e
+                            ));
+                            op.complete_write(&dest);
+                            // The store copy has no outside links left to skip
+                            place::copy_dir(&canonical, &dest, &agent.id, &scope_of(&agent.id))?;
+                            InstallMode::Copy
+                        }
+                    };
+                    op.complete_write(&dest);
+                    installed.push(InstalledSkill {
+                        name: skill.name.clone(),
+                        agent: agent.id.clone(),
+                        path: Some(dest.to_string_lossy().to_string()),
+                        mode,
+                        linked_to: (mode == InstallMode::Symlink)
+                            .then(|| canonical.to_string_lossy().to_string()),
+                        digest: digest.clone(),
+                        subpath: subpath.clone(),
+                    });
+                }
+            }
+        }
+        op.log(format!("Installed {}", skill.name));
+        op.set_phase(
+            Phase::Installing,
+            40 + (60 * (index + 1) / skills.len()) as u8,
+        );
+    }
+
+    let reference = match &source {
+        SkillSource::Git { reference, .. } => reference.clone(),
+        _ => None,
+    };
+    Ok(InstallReport {
+        source: request.source.clone(),
+        reference,
+        commit: fetched.commit().map(str::to_string),
+        installed,
+        skipped,
+    })
+}
+
+fn has_skill_file(path: &Path) -> bool {
+    path.join(SKILL_FILE).exists()
+}
+
+pub fn remove(
+    request: &RemoveRequest,
+    backend: Backend,
+    registry: &AgentRegistry,
+    op: &Operation,
+) -> Result<RemoveReport> {
+    op.set_phase(Phase::Removing, 10);
+    if backend == Backend::Npx {
+        return npx::remove(request, op);
+    }
+
+    let home = paths::home_dir()?;
+    let mut targets = target_dirs(
+        registry,
+        &request.agents,
+        request.global,
+        &home,
+        request.project.as_deref(),
+    )?;
+    let global = registry.require(GLOBAL_AGENT)?;
+    let store_dir = agent_dir(global, &home, request.project.as_deref());
+    let removes_store = request.global || request.agents.iter().any(|a| a == GLOBAL_AGENT);
+    for (agent, dir) in targets.iter_mut() {
+        if let Err(e) = refuse_store_dir(&agent.id, dir, &store_dir) {
+            if !removes_store {
+                return Err(e);
+            }
+            // Removing from the store was asked for anyway
+            *agent = global;
+        }
+    }
+    // Work out everything to remove first, so a copy that is still linked
+    // from elsewhere fails the request before anything is touched
+    let mut planned = Vec::new();
+    for (agent, dir) in targets {
+        let names: Vec<String> = if request.remove_all {
+            fs::read_dir(&dir)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|e| has_skill_file(&e.path()))
+                        .map(|e| e.file_name().to_string_lossy().to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            request.skill_ids.clone()
+        };
+
+        let scope = match request.project.as_deref() {
+            Some(root) => SkillScope::project(root),
+            None => SkillScope::for_home_agent(&agent.id),
+        };
+        for name in names {
+            let path = dir.join(&name);
+            planned.push((agent, scope.clone(), name, path));
+        }
+    }
+    let removing: Vec<PathBuf> = planned.iter().map(|(.., path)| path.clone()).collect();
+    let mut cascaded = Vec::new();
+    for (.., path) in &planned {
+        cascaded.push(links::check_dependents(path, &removing, request.cascade)?);
+    }
+
+    let mut report = RemoveReport::default();
+    for ((agent, scope, name, path), dependents) in planned.into_iter().zip(cascaded) {
+        op.check()?;
+        if fs::symlink_metadata(&path).is_err() {
+            report.missing.push(RemovedSkill {
+                name,
+                agent: agent.id.clone(),
+                path: Some(path.to_string_lossy().to_string()),
+                trash_id: None,
+                link_target: None,
+            });
+            continue;
+        }
+        for dependent in dependents {
+            report.removed.push(trash_skill(
+                Path::new(&dependent.path),
+                &name,
+                &dependent.agent,
+                &dependent.scope,
+                op,
+            )?);
+        }
+        report
+            .removed
+            .push(trash_skill(&path, &name, &agent.id, &scope, op)?);
+    }
+
+    Ok(report)
+}
+
+/// Moves a skill entry to the trash. For a link only the link goes.
+fn trash_skill(
+    path: &Path,
+    name: &str,
+    agent: &str,
+    scope: &SkillScope,
+    op: &Operation,
+) -> Result<RemovedSkill> {
+    let trashed = trash::move_to_trash(path, agent, scope)?;
+    match &trashed.link_target {
+        Some(target) => op.log(format!("Removed link {} -> {}", path.display(), target)),
+        None => op.log(format!("Moved {} from {} to the trash", name, agent)),
+    }
+    Ok(RemovedSkill {
+        name: name.to_string(),
+        agent: agent.to_string(),
+        path: Some(path.to_string_lossy().to_string()),
+        trash_id: Some(trashed.id),
+        link_target: trashed.link_target,
+    })
+}
+
+/// Removes a skill from the shared store together with the agent links pointing at it.
+pub fn remove_global(
+    id: &str,
+    backend: Backend,
+    registry: &AgentRegistry,
+    op: &Operation,
+) -> Result<RemoveReport> {
+    op.set_phase(Phase::Removing, 10);
+    if backend == Backend::Npx {
+        return npx::remove_global(id, op);
+    }
+
+    let home = paths::home_dir()?;
+    let canonical = registry.require(GLOBAL_AGENT)?.skills_dir(&home).join(id);
+    let mut report = RemoveReport::default();
+
+    if fs::symlink_metadata(&canonical).is_err() {
+        report.missing.push(RemovedSkill {
+            name: id.to_string(),
+            agent: GLOBAL_AGENT.to_string(),
+            path: Some(canonical.to_string_lossy().to_string()),
+            trash_id: None,
+            link_target: None,
+        });
+        return Ok(report);
+    }
+    for dependent in links::check_dependents(&canonical, &[], true)? {
+        report.removed.push(trash_skill(
+            Path::new(&dependent.path),
+            id,
+            &dependent.agent,
+            &dependent.scope,
+            op,
+        )?);
+    }
+    report.removed.push(trash_skill(
+        &canonical,
+        id,
+        GLOBAL_AGENT,
+        &SkillScope::Global,
+        op,
+    )?);
+
+    Ok(report)
+}
+
+/// One entry of a bulk import.
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct ImportEntry {
+    pub source: String,
+    #[serde(default)]
+    pub skill: Option<String>,
+    #[serde(default)]
+    pub agents: Vec<String>,
+    #[serde(default)]
+    pub global: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ImportResult {
+    pub source: String,
+    pub skill: Option<String>,
+    pub report: Option<InstallReport>,
+    pub error: Option<SkillsError>,
+}
+
+/// Installs several sources in one operation. Failures don't stop the batch.
+pub fn import(
+    entries: &[ImportEntry],
+    mode: InstallMode,
+    project: Option<&Path>,
+    backend: Backend,
+    registry: &AgentRegistry,
+    op: &Operation,
+) -> Vec<ImportResult> {
+    let total = entries.len().max(1);
+    let mut results = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(reason) = op.interrupted() {
+            // Report the rest as not installed rather than dropping them
+            results.push(ImportResult {
+                source: entry.source.clone(),
+                skill: entry.skill.clone(),
+                report: None,
+                error: Some(reason.into()),
+            });
+            continue;
+        }
+        op.log(format!(
+            "Installing {}/{}: {}",
+            index + 1,
+            entries.len(),
+            entry.source
+        ));
+        let request = InstallRequest {
+            source: entry.source.clone(),
+            reference: None,
+            skill: entry.skill.clone(),
+            global: entry.global,
+            agents: entry.agents.clone(),
+            mode,
+            project: project.map(Path::to_path_buf),
+            auto_confirm: true,
+            frozen: false,
+        };
+        let step = op.scoped(
+            (100 * index / total) as u8,
+            (100 * (index + 1) / total) as u8,
+        );
+        let result = install(&request, backend, registry, &step);
+        if let Err(ref e) = result {
+            op.log(format!("Failed to install {}: {}", entry.source, e));
+        }
+        results.push(ImportResult {
+            source: entry.source.clone(),
+            skill: entry.skill.clone(),
+            error: result.as_ref().err().cloned(),
+            report: result.ok(),
+        });
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{operation, registry, sandbox, write_skill};
+
+    fn remove_request(project: &Path, agents: &[&str]) -> RemoveRequest {
+        RemoveRequest {
+            skill_ids: vec!["pdf".to_string()],
+            global: false,
+            agents: agents.iter().map(|a| a.to_string()).collect(),
+            remove_all: false,
+            project: Some(project.to_path_buf()),
+            auto_confirm: true,
+            cascade: false,
+        }
+    }
+
+    #[test]
+    fn removing_from_an_agent_that_uses_the_store_is_refused() {
+        sandbox();
+        let project = tempfile::tempdir().unwrap();
+        let store_copy = project.path().join(".agents/skills/pdf");
+        write_skill(&store_copy, "pdf");
+        let registry = registry();
+
+        let refused = remove(
+            &remove_request(project.path(), &["codex"]),
+            Backend::Native,
+            &registry,
+            &operation(),
+        );
+        assert!(matches!(refused, Err(SkillsError::InvalidInput(_))));
+        assert!(store_copy.join(SKILL_FILE).is_file());
+
+        let report = remove(
+            &remove_request(project.path(), &["codex", GLOBAL_AGENT]),
+            Backend::Native,
+            &registry,
+            &operation(),
+        )
+        .unwrap();
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.removed[0].agent, GLOBAL_AGENT);
+        assert!(fs::symlink_metadata(&store_copy).is_err());
+    }
+}
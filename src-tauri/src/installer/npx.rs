@@ -0,0 +1,172 @@
+//! Fallback backend that shells out to the `npx skills` CLI.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+use super::{
+    InstallReport, InstallRequest, InstalledSkill, RemoveReport, RemoveRequest, RemovedSkill,
+};
+use crate::agents::GLOBAL_AGENT;
+use crate::error::{Result, SkillsError};
+use crate::operations::Operation;
+
+fn run_npx(op: &Operation, args: &[String], cwd: Option<&Path>) -> Result<String> {
+    op.log(format!("Full command: npx {}", args.join(" ")));
+
+    // Windows 需要通过 cmd.exe 来执行 npx
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "npx"]);
+        c.args(args);
+        c
+    } else {
+        let mut c = Command::new("npx");
+        c.args(args);
+        c
+    };
+
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+
+    // Newline on stdin as fallback input if flags don't cover everything
+    let output = op.run_command(&mut cmd, Some(b"\n"))?;
+    op.log(format!(
+        "Process completed with status: {:?}",
+        output.status
+    ));
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(SkillsError::CommandFailed {
+            command: "npx skills".to_string(),
+            message: output.stderr.trim().to_string(),
+        })
+    }
+}
+
+fn push_agent_flags(args: &mut Vec<String>, agents: &[String]) {
+    for agent in agents {
+        // 'global' is handled by the --global flag
+        if agent == GLOBAL_AGENT {
+            continue;
+        }
+        args.push("--agent".to_string());
+        args.push(agent.clone());
+    }
+}
+
+pub fn install(request: &InstallRequest, op: &Operation) -> Result<InstallReport> {
+    let mut args = vec![
+        "skills".to_string(),
+        "add".to_string(),
+        request.source.clone(),
+    ];
+
+    if let Some(ref skill_name) = request.skill {
+        args.push("--skill".to_string());
+        args.push(skill_name.clone());
+    }
+    if request.global && request.project.is_none() {
+        args.push("--global".to_string());
+    }
+    push_agent_flags(&mut args, &request.agents);
+    if request.auto_confirm {
+        args.push("--yes".to_string());
+    }
+
+    let stdout_str = run_npx(op, &args, request.project.as_deref())?;
+
+    // The CLI has no machine readable output, so pick installed skills out of
+    // paths like ~\.agents\skills\agent-browser
+    let re = regex::Regex::new(r"[~\\]\.agents[\\/]skills[\\/]([a-zA-Z0-9_-]+)").unwrap();
+    let mut names: HashSet<String> = re
+        .captures_iter(&stdout_str)
+        .filter_map(|cap| cap.get(1).map(|m| m.as_str().to_string()))
+        .collect();
+    if let Some(ref specific_skill) = request.skill {
+        names.insert(specific_skill.clone());
+    }
+
+    let mut names: Vec<String> = names.into_iter().collect();
+    names.sort();
+
+    Ok(InstallReport {
+        source: request.source.clone(),
+        reference: None,
+        commit: None,
+        installed: names
+            .into_iter()
+            .map(|name| InstalledSkill {
+                name,
+                agent: GLOBAL_AGENT.to_string(),
+                path: None,
+                mode: request.mode,
+                linked_to: None,
+                digest: None,
+                subpath: None,
+            })
+            .collect(),
+        skipped: Vec::new(),
+    })
+}
+
+pub fn remove(request: &RemoveRequest, op: &Operation) -> Result<RemoveReport> {
+    let mut args = vec!["skills".to_string(), "remove".to_string()];
+    let global = request.global && request.project.is_none();
+
+    if request.remove_all {
+        args.push("--all".to_string());
+    } else {
+        args.extend(request.skill_ids.iter().cloned());
+    }
+    if global {
+        args.push("--global".to_string());
+    }
+    push_agent_flags(&mut args, &request.agents);
+    if request.auto_confirm {
+        args.push("--yes".to_string());
+    }
+
+    run_npx(op, &args, request.project.as_deref())?;
+
+    // The CLI doesn't say what it removed, so report what was requested
+    let mut targets: Vec<&str> = request.agents.iter().map(String::as_str).collect();
+    if global {
+        targets.push(GLOBAL_AGENT);
+    }
+    let mut report = RemoveReport::default();
+    for agent in targets {
+        for id in &request.skill_ids {
+            report.removed.push(RemovedSkill {
+                name: id.clone(),
+                agent: agent.to_string(),
+                path: None,
+                trash_id: None,
+                link_target: None,
+            });
+        }
+    }
+    Ok(report)
+}
+
+pub fn remove_global(id: &str, op: &Operation) -> Result<RemoveReport> {
+    let args: Vec<String> = ["skills", "remove", "-g", id]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    run_npx(op, &args, None)?;
+
+    Ok(RemoveReport {
+        removed: vec![RemovedSkill {
+            name: id.to_string(),
+            agent: GLOBAL_AGENT.to_string(),
+            path: None,
+            trash_id: None,
+            link_target: None,
+        }],
+        missing: Vec::new(),
+    })
+}
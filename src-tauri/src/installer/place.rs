@@ -0,0 +1,224 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::discover::SkippedSkill;
+
+use crate::error::{Result, SkillsError};
+use crate::operations::Operation;
+use crate::scope::SkillScope;
+use crate::trash::{self, TrashEntry};
+
+/// What [`copy_dir`] did besides copying.
+#[derive(Debug, Default)]
+pub struct Copied {
+    /// Trash entry of the copy that was at the destination
+    pub replaced: Option<TrashEntry>,
+    /// Links in the source that point outside it, left out of the copy
+    pub skipped_links: Vec<PathBuf>,
+}
+
+impl Copied {
+    /// Logs each skipped link, and turns them into install report entries.
+    pub(crate) fn report_skipped(&self, op: &Operation) -> Vec<SkippedSkill> {
+        self.skipped_links
+            .iter()
+            .map(|path| {
+                op.log(format!(
+                    "Skipped {}: links outside the skill",
+                    path.display()
+                ));
+                SkippedSkill {
+                    path: path.to_string_lossy().to_string(),
+                    reason: "Links outside the skill, not copied".to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// Removes a skill entry without following symlinks into their target.
+pub fn remove_entry(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.file_type().is_symlink() {
+        // Directory symlinks are directories on Windows and files elsewhere
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+    } else if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Copies a skill directory to `dest`, replacing whatever is there. `.git`
+/// is skipped.
+///
+/// The copy is made in a hidden sibling directory and renamed over `dest`
+/// once complete, so a failed copy leaves the old one alone. The old one is
+/// moved to the trash as `agent`'s skill in `scope` rather than deleted,
+/// since it may hold local edits.
+pub fn copy_dir(src: &Path, dest: &Path, agent: &str, scope: &SkillScope) -> Result<Copied> {
+    let parent = dest
+        .parent()
+        .ok_or_else(|| SkillsError::Other(format!("{} has no parent", dest.display())))?;
+    fs::create_dir_all(parent).map_err(|e| SkillsError::io(parent, e))?;
+    let name = dest.file_name().unwrap_or_default().to_string_lossy();
+    // Removed again when dropped, should the copy fail
+    let staging = tempfile::Builder::new()
+        .prefix(&format!(".{}.", name))
+        .tempdir_in(parent)
+        .map_err(|e| SkillsError::io(parent, e))?;
+    // Same permissions as a directory made by hand, not the temp dir's 0700
+    let permissions = fs::metadata(src)
+        .map_err(|e| SkillsError::io(src, e))?
+        .permissions();
+    fs::set_permissions(staging.path(), permissions)
+        .map_err(|e| SkillsError::io(staging.path(), e))?;
+    let skipped_links = copy_contents(src, staging.path())?;
+
+    let replaced = match fs::symlink_metadata(dest) {
+        Ok(_) => Some(trash::move_to_trash(dest, agent, scope)?),
+        Err(_) => None,
+    };
+    if let Err(e) = fs::rename(staging.path(), dest) {
+        if let Some(entry) = replaced {
+            let _ = trash::restore(&entry.id);
+        }
+        return Err(SkillsError::io(dest, e));
+    }
+    // Now at `dest`; nothing left for the guard to clean up
+    let _ = staging.keep();
+    Ok(Copied {
+        replaced,
+        skipped_links,
+    })
+}
+
+/// Copies the files of `src` into the existing directory `dest`.
+///
+/// Symlinks aren't followed, so a skill can't pull in files from elsewhere
+/// (`loot -> ~/.ssh`). Relative links that stay inside `src` are recreated
+/// as links; any other link is skipped and returned.
+fn copy_contents(src: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
+    let mut skipped = Vec::new();
+    let root = fs::canonicalize(src).map_err(|e| SkillsError::io(src, e))?;
+    let walker = WalkDir::new(src)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git");
+
+    for entry in walker {
+        let entry = entry.map_err(|e| {
+            let path = e.path().unwrap_or(src).to_path_buf();
+            match e.into_io_error() {
+                Some(err) => SkillsError::io(&path, err),
+                None => SkillsError::Io {
+                    path: Some(path),
+                    message: "Symlink loop".to_string(),
+                },
+            }
+        })?;
+        let relative = entry.path().strip_prefix(src).unwrap_or(entry.path());
+        let target = dest.join(relative);
+
+        if entry.path_is_symlink() {
+            let link = fs::read_link(entry.path()).map_err(|e| SkillsError::io(entry.path(), e))?;
+            let inside = link.is_relative()
+                && fs::canonicalize(entry.path()).is_ok_and(|resolved| resolved.starts_with(&root));
+            if !inside {
+                skipped.push(entry.path().to_path_buf());
+                continue;
+            }
+            copy_link(&link, entry.path(), &target).map_err(|e| SkillsError::io(&target, e))?;
+        } else if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).map_err(|e| SkillsError::io(&target, e))?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| SkillsError::io(&target, e))?;
+        }
+    }
+    Ok(skipped)
+}
+
+/// Recreates the symlink `original`, pointing at `link`, at `target`.
+pub(crate) fn copy_link(link: &Path, original: &Path, target: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let _ = original;
+        std::os::unix::fs::symlink(link, target)
+    }
+    #[cfg(windows)]
+    {
+        if original.is_dir() {
+            std::os::windows::fs::symlink_dir(link, target)
+        } else {
+            std::os::windows::fs::symlink_file(link, target)
+        }
+    }
+}
+
+/// Points `link` at `target`, replacing whatever is at `link`.
+pub fn link_dir(target: &Path, link: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(link).is_ok() {
+        // Already linked to the right place, nothing to do
+        if is_symlink(link) && fs::read_link(link).ok().as_deref() == Some(target) {
+            return Ok(());
+        }
+        remove_entry(link)?;
+    }
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::symlink_dir(target, link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{operation, sandbox, write_skill};
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_links_out_of_the_skill() {
+        sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        write_skill(&src, "pdf");
+        fs::create_dir_all(src.join("sub")).unwrap();
+        fs::write(src.join("sub/a.txt"), "a").unwrap();
+        fs::write(dir.path().join("secret"), "s").unwrap();
+        std::os::unix::fs::symlink("sub/a.txt", src.join("inner")).unwrap();
+        std::os::unix::fs::symlink("../secret", src.join("loot")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("secret"), src.join("absolute")).unwrap();
+
+        let dest = dir.path().join("skills/pdf");
+        let copied = copy_dir(&src, &dest, "claude-code", &SkillScope::Global).unwrap();
+        assert!(copied.replaced.is_none());
+        let mut skipped = copied.skipped_links.clone();
+        skipped.sort();
+        assert_eq!(skipped, [src.join("absolute"), src.join("loot")]);
+        assert_eq!(
+            fs::read_link(dest.join("inner")).unwrap(),
+            Path::new("sub/a.txt")
+        );
+        assert!(fs::symlink_metadata(dest.join("loot")).is_err());
+
+        let reported = copied.report_skipped(&operation());
+        assert_eq!(reported.len(), 2);
+    }
+}
@@ -0,0 +1,303 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::error::{Result, SkillsError};
+use crate::git;
+use crate::operations::Operation;
+
+/// Where skills are installed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillSource {
+    Git {
+        url: String,
+        reference: Option<String>,
+        subpath: Option<String>,
+    },
+    Local(PathBuf),
+    Tarball(String),
+}
+
+/// A source that has been cloned, extracted or located on disk.
+/// Temporary checkouts are deleted when this is dropped.
+pub struct FetchedSource {
+    root: PathBuf,
+    commit: Option<String>,
+    _workdir: Option<TempDir>,
+}
+
+impl FetchedSource {
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The checked out commit, for git sources.
+    pub fn commit(&self) -> Option<&str> {
+        self.commit.as_deref()
+    }
+}
+
+const TARBALL_EXTENSIONS: &[&str] = &[".tar.gz", ".tgz", ".tar"];
+
+fn is_tarball(input: &str) -> bool {
+    let lower = input.to_lowercase();
+    TARBALL_EXTENSIONS.iter().any(|ext| lower.ends_with(ext))
+}
+
+fn expand_home(input: &str) -> PathBuf {
+    match input.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(input)),
+        None => PathBuf::from(input),
+    }
+}
+
+impl SkillSource {
+    /// Recognises git URLs, `owner/repo[/subpath]` GitHub shorthand,
+    /// GitHub/GitLab `tree/<ref>/<path>` links, local directories and tarballs.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(SkillsError::InvalidInput(
+                "Source must not be empty".to_string(),
+            ));
+        }
+
+        let local = expand_home(input);
+        if local.is_dir() {
+            return Ok(SkillSource::Local(local));
+        }
+        if local.is_file() {
+            if is_tarball(input) {
+                return Ok(SkillSource::Tarball(local.to_string_lossy().to_string()));
+            }
+            return Err(SkillsError::InvalidInput(format!(
+                "Unsupported source file: {}",
+                input
+            )));
+        }
+
+        let is_url = input.starts_with("http://") || input.starts_with("https://");
+        if is_url && is_tarball(input) {
+            return Ok(SkillSource::Tarball(input.to_string()));
+        }
+
+        let tree_re = regex::Regex::new(
+            r"^https?://(github\.com|gitlab\.com)/([^/]+)/([^/]+?)(?:\.git)?/(?:-/)?tree/([^/]+)(?:/(.+?))?/?$",
+        )
+        .unwrap();
+        if let Some(cap) = tree_re.captures(input) {
+            return Ok(SkillSource::Git {
+                url: format!("https://{}/{}/{}.git", &cap[1], &cap[2], &cap[3]),
+                reference: Some(cap[4].to_string()),
+                subpath: cap.get(5).map(|m| m.as_str().to_string()),
+            });
+        }
+
+        if is_url
+            || input.starts_with("git@")
+            || input.starts_with("ssh://")
+            || input.ends_with(".git")
+        {
+            return Ok(SkillSource::Git {
+                url: input.to_string(),
+                reference: None,
+                subpath: None,
+            });
+        }
+
+        let shorthand_re =
+            regex::Regex::new(r"^([A-Za-z0-9_.-]+)/([A-Za-z0-9_.-]+)(?:/(\S+?))?/?$").unwrap();
+        if let Some(cap) = shorthand_re.captures(input) {
+            return Ok(SkillSource::Git {
+                url: format!("https://github.com/{}/{}.git", &cap[1], &cap[2]),
+                reference: None,
+                subpath: cap.get(3).map(|m| m.as_str().to_string()),
+            });
+        }
+
+        Err(SkillsError::InvalidInput(format!(
+            "Unrecognized skill source: {}",
+            input
+        )))
+    }
+
+    /// Checks out `reference` instead of the ref in the source, if any.
+    /// Only git sources, including local repositories, can be pinned.
+    pub fn pinned(self, reference: Option<&str>) -> Result<Self> {
+        let Some(pin) = reference else {
+            return Ok(self);
+        };
+        match self {
+            SkillSource::Git { url, subpath, .. } => Ok(SkillSource::Git {
+                url,
+                reference: Some(pin.to_string()),
+                subpath,
+            }),
+            SkillSource::Local(path) if is_git_repo(&path) => Ok(SkillSource::Git {
+                url: path.to_string_lossy().to_string(),
+                reference: Some(pin.to_string()),
+                subpath: None,
+            }),
+            _ => Err(SkillsError::InvalidInput(format!(
+                "Only git sources can be pinned to a ref, not {:?}",
+                self
+            ))),
+        }
+    }
+
+    /// Makes the source available on disk.
+    pub fn fetch(&self, op: &Operation) -> Result<FetchedSource> {
+        match self {
+            SkillSource::Local(path) => Ok(FetchedSource {
+                root: path.clone(),
+                commit: None,
+                _workdir: None,
+            }),
+            SkillSource::Git {
+                url,
+                reference,
+                subpath,
+            } => {
+                let workdir = new_workdir()?;
+                let checkout = workdir.path().join("repo");
+                git::clone(url, reference.as_deref(), &checkout, op)?;
+                let commit = git::head(&checkout).ok().map(|head| head.commit);
+                let root = match subpath {
+                    Some(subpath) => checkout.join(subpath),
+                    None => checkout,
+                };
+                if !root.exists() {
+                    return Err(SkillsError::NotFound { path: root });
+                }
+                Ok(FetchedSource {
+                    root,
+                    commit,
+                    _workdir: Some(workdir),
+                })
+            }
+            SkillSource::Tarball(location) => {
+                let workdir = new_workdir()?;
+                let root = workdir.path().join("contents");
+                extract_tarball(location, &root, op)?;
+                Ok(FetchedSource {
+                    root,
+                    commit: None,
+                    _workdir: Some(workdir),
+                })
+            }
+        }
+    }
+}
+
+fn new_workdir() -> Result<TempDir> {
+    tempfile::Builder::new()
+        .prefix("skills-manager-")
+        .tempdir()
+        .map_err(|e| SkillsError::io(std::env::temp_dir(), e))
+}
+
+/// A working tree or a bare repository.
+fn is_git_repo(path: &Path) -> bool {
+    path.join(".git").exists() || (path.join("HEAD").is_file() && path.join("objects").is_dir())
+}
+
+fn extract_tarball(location: &str, dest: &Path, op: &Operation) -> Result<()> {
+    fs::create_dir_all(dest).map_err(|e| SkillsError::io(dest, e))?;
+
+    let reader: Box<dyn std::io::Read> = if location.starts_with("http://")
+        || location.starts_with("https://")
+    {
+        op.log(format!("Downloading {}", location));
+        // A stalled download never reaches the checks between entries
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(left) = op.remaining() {
+            agent = agent.timeout_connect(left).timeout_read(left).timeout(left);
+        }
+        let response = agent
+            .build()
+            .get(location)
+            .call()
+            .map_err(|e| match op.interrupted() {
+                Some(reason) => reason.into(),
+                None => SkillsError::Network {
+                    url: location.to_string(),
+                    message: e.to_string(),
+                },
+            })?;
+        Box::new(response.into_reader())
+    } else {
+        Box::new(fs::File::open(location).map_err(|e| SkillsError::io(location, e))?)
+    };
+
+    let reader: Box<dyn std::io::Read> = if location.to_lowercase().ends_with(".tar") {
+        reader
+    } else {
+        Box::new(flate2::read::GzDecoder::new(reader))
+    };
+
+    op.log(format!("Extracting {}", location));
+    let failed = |e: std::io::Error| match op.interrupted() {
+        Some(reason) => reason.into(),
+        None => SkillsError::Io {
+            path: Some(PathBuf::from(location)),
+            message: format!("Failed to extract archive: {}", e),
+        },
+    };
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(failed)? {
+        op.check()?;
+        entry
+            .and_then(|mut entry| entry.unpack_in(dest))
+            .map_err(failed)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{operation, write_skill};
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn extracts_a_local_tarball() {
+        let dir = tempfile::tempdir().unwrap();
+        write_skill(&dir.path().join("src/pdf"), "pdf");
+        let tarball = dir.path().join("skills.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&tarball).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        builder
+            .append_dir_all("pdf", dir.path().join("src/pdf"))
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let dest = dir.path().join("out");
+        extract_tarball(tarball.to_str().unwrap(), &dest, &operation()).unwrap();
+        assert!(dest.join("pdf/SKILL.md").is_file());
+    }
+
+    #[test]
+    fn stalled_download_times_out() {
+        // Accepts connections and never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/skills.tar.gz", listener.local_addr().unwrap());
+        let dest = tempfile::tempdir().unwrap();
+        let op = operation().with_timeout(Some(Duration::from_secs(1)));
+
+        let started = Instant::now();
+        let result = extract_tarball(&url, dest.path(), &op);
+        assert!(
+            matches!(result, Err(SkillsError::TimedOut { .. })),
+            "{result:?}"
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+        drop(listener);
+    }
+}
@@ -0,0 +1,403 @@
+//! Symlinked agent entries and the copies they point to.
+//!
+//! A symlink install links each agent's entry to one real copy, usually in
+//! the shared `~/.agents/skills` store. Removing that copy breaks every link
+//! to it, so removal looks its dependents up first, and the doctor finds
+//! links that no longer lead to a usable copy.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agents::{AgentRegistry, GLOBAL_AGENT};
+use crate::error::{Result, SkillsError};
+use crate::installer::place;
+use crate::paths;
+use crate::scanner;
+use crate::scope::SkillScope;
+use crate::skills;
+use crate::source_manager;
+use crate::trash;
+use crate::workspaces;
+
+/// An agent entry that is a symlink to some other copy of a skill.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Dependent {
+    pub agent: String,
+    pub scope: SkillScope,
+    pub path: String,
+}
+
+pub fn is_link(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// The directory the symlink at `path` resolves to. `None` when `path`
+/// isn't a symlink or its target is gone.
+pub fn resolve(path: &Path) -> Option<PathBuf> {
+    if !is_link(path) {
+        return None;
+    }
+    fs::canonicalize(path).ok()
+}
+
+/// Links in any agent's skills directory, at home or in a registered
+/// workspace, that resolve to `copy`.
+pub fn dependents(copy: &Path) -> Result<Vec<Dependent>> {
+    let copy = fs::canonicalize(copy).map_err(|e| SkillsError::io(copy, e))?;
+    let mut found = Vec::new();
+    for dir in skills::local_scan_dirs()? {
+        let Ok(entries) = fs::read_dir(&dir.dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if resolve(&path).as_ref() == Some(&copy) {
+                found.push(Dependent {
+                    agent: dir.agent.clone(),
+                    scope: dir.scope.clone(),
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Fails with [`SkillsError::HasDependents`] when `path` is a real copy
+/// that links other than `removing` still point to. Returns those links
+/// when `cascade` allows removing them too.
+pub fn check_dependents(
+    path: &Path,
+    removing: &[PathBuf],
+    cascade: bool,
+) -> Result<Vec<Dependent>> {
+    if is_link(path) || !path.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dependents = dependents(path)?;
+    dependents.retain(|d| !removing.iter().any(|p| Path::new(&d.path) == p));
+    if !dependents.is_empty() && !cascade {
+        return Err(SkillsError::HasDependents {
+            path: path.to_path_buf(),
+            dependents: dependents.iter().map(|d| PathBuf::from(&d.path)).collect(),
+        });
+    }
+    Ok(dependents)
+}
+
+/// What is wrong with a skill link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkProblem {
+    /// The target no longer exists
+    Dangling,
+    /// The target exists but isn't in a global store, so it can move or
+    /// disappear without the manager knowing
+    OutsideStore,
+    /// Following the link leads back to itself
+    Loop,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BrokenLink {
+    pub agent: String,
+    pub scope: SkillScope,
+    pub path: String,
+    /// What the link points to, as written
+    pub target: String,
+    pub problem: LinkProblem,
+    /// The global store copy of the same skill, which the link can be
+    /// pointed back at
+    pub store_copy: Option<String>,
+}
+
+/// A link [`repair`] fixed.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RepairedLink {
+    #[serde(flatten)]
+    pub link: BrokenLink,
+    /// Links inside the skill that pointed outside it and were left out of
+    /// the copy that replaced it
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+}
+
+/// How to repair a [`BrokenLink`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkFix {
+    /// Point the link at the global store copy
+    Relink,
+    /// Replace the link with a real copy of what it points to, or of the
+    /// store copy when the target is gone
+    Copy,
+    /// Move the link to the trash
+    Delete,
+}
+
+impl LinkFix {
+    pub fn parse(fix: &str) -> Result<Self> {
+        match fix {
+            "relink" => Ok(LinkFix::Relink),
+            "copy" => Ok(LinkFix::Copy),
+            "delete" => Ok(LinkFix::Delete),
+            other => Err(SkillsError::InvalidInput(format!(
+                "Unknown fix '{}', expected relink, copy or delete",
+                other
+            ))),
+        }
+    }
+}
+
+/// Longest chain of links followed before calling it a loop, like the
+/// kernel's limit.
+const MAX_HOPS: usize = 40;
+
+/// Follows the link at `path` one hop at a time, so a loop can be told
+/// apart from a missing target.
+fn follow(path: &Path) -> std::result::Result<PathBuf, LinkProblem> {
+    let mut current = path.to_path_buf();
+    let mut seen = HashSet::new();
+    while is_link(&current) {
+        if !seen.insert(current.clone()) || seen.len() > MAX_HOPS {
+            return Err(LinkProblem::Loop);
+        }
+        let target = fs::read_link(&current).map_err(|_| LinkProblem::Dangling)?;
+        current = current.parent().unwrap_or(Path::new("")).join(target);
+    }
+    fs::canonicalize(&current).map_err(|_| LinkProblem::Dangling)
+}
+
+/// The global store of the home directory and of each workspace.
+fn store_dirs(registry: &AgentRegistry) -> Result<Vec<(SkillScope, PathBuf)>> {
+    let home = paths::home_dir()?;
+    let store = registry.require(GLOBAL_AGENT)?;
+    let mut dirs = vec![(SkillScope::Global, store.skills_dir(&home))];
+    for root in workspaces::list_workspaces()? {
+        dirs.push((SkillScope::project(&root), store.project_skills_dir(&root)));
+    }
+    Ok(dirs)
+}
+
+/// Skill links that are dangling, loop, or point outside every global store.
+pub fn broken_links() -> Result<Vec<BrokenLink>> {
+    let registry = AgentRegistry::load()?;
+    let stores = store_dirs(&registry)?;
+    let resolved_stores: Vec<PathBuf> = stores
+        .iter()
+        .filter_map(|(_, dir)| fs::canonicalize(dir).ok())
+        .collect();
+
+    let mut broken = Vec::new();
+    for dir in skills::local_scan_dirs()? {
+        let is_store = stores.iter().any(|(_, store)| store == &dir.dir);
+        let Ok(entries) = fs::read_dir(&dir.dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !is_link(&path) {
+                continue;
+            }
+            let problem = match follow(&path) {
+                Err(problem) => problem,
+                // A store may hold links to checkouts elsewhere
+                Ok(_) if is_store => continue,
+                Ok(target) if resolved_stores.iter().any(|s| target.starts_with(s)) => continue,
+                Ok(_) => LinkProblem::OutsideStore,
+            };
+            let name = entry.file_name();
+            broken.push(BrokenLink {
+                agent: dir.agent.clone(),
+                scope: dir.scope.clone(),
+                path: path.to_string_lossy().to_string(),
+                target: fs::read_link(&path)
+                    .map(|t| t.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                problem,
+                store_copy: store_copy(&stores, &dir.scope, &name)
+                    .filter(|copy| copy != &path)
+                    .map(|copy| copy.to_string_lossy().to_string()),
+            });
+        }
+    }
+    Ok(broken)
+}
+
+/// The real copy named `name` in the store for `scope`: the project's own
+/// store first, then the home one.
+fn store_copy(
+    stores: &[(SkillScope, PathBuf)],
+    scope: &SkillScope,
+    name: &std::ffi::OsStr,
+) -> Option<PathBuf> {
+    let own = stores.iter().find(|(s, _)| match scope {
+        SkillScope::Project { .. } => s == scope,
+        _ => false,
+    });
+    own.into_iter()
+        .chain(stores.first())
+        .map(|(_, dir)| dir.join(name))
+        .find(|copy| !is_link(copy) && copy.is_dir())
+}
+
+/// Applies `fix` to the broken link at `path`.
+pub fn repair(path: &Path, fix: LinkFix) -> Result<RepairedLink> {
+    let link = broken_links()?
+        .into_iter()
+        .find(|link| Path::new(&link.path) == path)
+        .ok_or_else(|| {
+            SkillsError::InvalidInput(format!("{} is not a broken skill link", path.display()))
+        })?;
+    let store_copy = link.store_copy.as_deref().map(Path::new);
+    let no_copy = || {
+        SkillsError::InvalidInput(format!(
+            "There is no global copy of {} to use",
+            path.display()
+        ))
+    };
+
+    let mut skipped = Vec::new();
+    match fix {
+        LinkFix::Relink => {
+            let copy = store_copy.ok_or_else(no_copy)?;
+            place::link_dir(copy, path).map_err(|e| SkillsError::io(path, e))?;
+        }
+        LinkFix::Copy => {
+            let source = match link.problem {
+                LinkProblem::OutsideStore => {
+                    fs::canonicalize(path).map_err(|e| SkillsError::io(path, e))?
+                }
+                _ => store_copy.ok_or_else(no_copy)?.to_path_buf(),
+            };
+            // The copy takes the link's place, provenance included
+            let copied = place::copy_dir(&source, path, &link.agent, &link.scope)?;
+            if let Some(provenance) = copied.replaced.and_then(|entry| entry.provenance) {
+                source_manager::restore_entry(provenance)?;
+            }
+            skipped = copied
+                .skipped_links
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect();
+        }
+        LinkFix::Delete => {
+            trash::move_to_trash(path, &link.agent, &link.scope)?;
+        }
+    }
+    scanner::invalidate(path);
+    Ok(RepairedLink { link, skipped })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+    use crate::installer::{self, Backend, RemoveRequest};
+    use crate::test_support::{operation, registry, workspace, write_skill};
+
+    /// A store copy of `name` in the workspace and a claude-code link to it.
+    fn linked_skill(root: &Path, name: &str) -> (PathBuf, PathBuf) {
+        let copy = root.join(".agents/skills").join(name);
+        write_skill(&copy, name);
+        let link = root.join(".claude/skills").join(name);
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        symlink(&copy, &link).unwrap();
+        (copy, link)
+    }
+
+    #[test]
+    fn tells_links_from_copies() {
+        let workspace = workspace();
+        let (copy, link) = linked_skill(&workspace.root, "pdf");
+
+        assert!(is_link(&link));
+        assert!(!is_link(&copy));
+        assert_eq!(resolve(&link), Some(fs::canonicalize(&copy).unwrap()));
+        assert_eq!(resolve(&copy), None);
+
+        let found = dependents(&copy).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].agent, "claude-code");
+        assert_eq!(Path::new(&found[0].path), link);
+
+        assert!(matches!(
+            check_dependents(&copy, &[], false),
+            Err(SkillsError::HasDependents { .. })
+        ));
+        assert!(check_dependents(&copy, std::slice::from_ref(&link), false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(check_dependents(&copy, &[], true).unwrap().len(), 1);
+        // A link has no dependents of its own
+        assert!(check_dependents(&link, &[], false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn cascade_removes_links_with_the_copy() {
+        let workspace = workspace();
+        let (copy, link) = linked_skill(&workspace.root, "pdf");
+        let mut request = RemoveRequest {
+            skill_ids: vec!["pdf".to_string()],
+            global: true,
+            agents: Vec::new(),
+            remove_all: false,
+            project: Some(workspace.root.clone()),
+            auto_confirm: true,
+            cascade: false,
+        };
+
+        let refused = installer::remove(&request, Backend::Native, &registry(), &operation());
+        assert!(matches!(refused, Err(SkillsError::HasDependents { .. })));
+        assert!(copy.is_dir() && is_link(&link));
+
+        request.cascade = true;
+        let report =
+            installer::remove(&request, Backend::Native, &registry(), &operation()).unwrap();
+        assert_eq!(report.removed.len(), 2);
+        assert!(fs::symlink_metadata(&copy).is_err());
+        assert!(fs::symlink_metadata(&link).is_err());
+    }
+
+    #[test]
+    fn repairs_dangling_links() {
+        let workspace = workspace();
+        let root = &workspace.root;
+        let mut links = Vec::new();
+        for name in ["relink", "copy", "delete"] {
+            let (_, link) = linked_skill(root, name);
+            fs::remove_file(&link).unwrap();
+            symlink(root.join("gone"), &link).unwrap();
+            links.push(link);
+        }
+
+        let broken = broken_links().unwrap();
+        for link in &links {
+            let found = broken.iter().find(|b| Path::new(&b.path) == link).unwrap();
+            assert_eq!(found.problem, LinkProblem::Dangling);
+            assert!(found.store_copy.is_some());
+        }
+
+        repair(&links[0], LinkFix::Relink).unwrap();
+        assert_eq!(
+            fs::read_link(&links[0]).unwrap(),
+            root.join(".agents/skills/relink")
+        );
+
+        repair(&links[1], LinkFix::Copy).unwrap();
+        assert!(!is_link(&links[1]));
+        assert!(links[1].join("SKILL.md").is_file());
+
+        repair(&links[2], LinkFix::Delete).unwrap();
+        assert!(fs::symlink_metadata(&links[2]).is_err());
+
+        assert!(broken_links()
+            .unwrap()
+            .iter()
+            .all(|b| !links.iter().any(|link| Path::new(&b.path) == link)));
+    }
+}
@@ -0,0 +1,149 @@
+//! `skills.lock`: the exact commit and content digest of every installed
+//! skill, so the same skills can be installed again later or elsewhere.
+//!
+//! Project installs use `<project>/skills.lock`, which is meant to be
+//! committed next to `skills.toml`; the `skills.lock.lock` beside it only
+//! guards updates and can be ignored. Home directory installs use the one in
+//! the app's data directory. Every non-frozen install updates the file; a
+//! frozen install checks out the locked commit and refuses any skill whose
+//! digest differs from the recorded one.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::digest;
+use crate::error::{Result, SkillsError};
+use crate::installer::InstallReport;
+use crate::paths;
+use crate::util;
+
+pub const LOCK_FILE: &str = "skills.lock";
+
+/// Held next to the lockfile while it's updated, so installs running at the
+/// same time, in the app and on the command line, don't drop each other's
+/// entries.
+const WRITE_LOCK_FILE: &str = "skills.lock.lock";
+
+/// Version written by this build.
+const LOCK_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default, rename = "skill", skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<LockedSkill>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LockedSkill {
+    pub name: String,
+    /// What was passed to the installer
+    pub source: String,
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Resolved commit, for git sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Directory of the skill inside the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subpath: Option<String>,
+    /// [`digest::dir_digest`] of the skill directory
+    pub digest: String,
+}
+
+/// Where the lockfile for `project`, or the home directory, lives.
+pub fn lock_path(project: Option<&Path>) -> Result<PathBuf> {
+    match project {
+        Some(root) => Ok(root.join(LOCK_FILE)),
+        None => Ok(paths::data_dir()?.join(LOCK_FILE)),
+    }
+}
+
+impl Lockfile {
+    /// An empty lockfile when there is none yet.
+    pub fn load(project: Option<&Path>) -> Result<Self> {
+        let path = lock_path(project)?;
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+        let lock: Lockfile =
+            toml::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))?;
+        if lock.version > LOCK_VERSION {
+            return Err(SkillsError::invalid_config(
+                &path,
+                format!(
+                    "version {} is newer than this app supports ({})",
+                    lock.version, LOCK_VERSION
+                ),
+            ));
+        }
+        Ok(lock)
+    }
+
+    /// Writes through a temp file so a crash never leaves a torn lockfile.
+    pub fn save(&self, project: Option<&Path>) -> Result<()> {
+        let path = lock_path(project)?;
+        paths::ensure_parent(&path)?;
+        let mut lock = self.clone();
+        lock.version = LOCK_VERSION;
+        lock.skills
+            .sort_by(|a, b| (&a.name, &a.source).cmp(&(&b.name, &b.source)));
+        let content =
+            toml::to_string_pretty(&lock).map_err(|e| SkillsError::Other(e.to_string()))?;
+        util::write_atomic(&path, content.as_bytes())
+    }
+
+    pub fn find(&self, source: &str, name: &str) -> Option<&LockedSkill> {
+        self.skills
+            .iter()
+            .find(|s| s.source == source && s.name == name)
+    }
+
+    /// The commit every locked skill from `source` was installed at.
+    pub fn commit_for(&self, source: &str) -> Option<&str> {
+        self.skills
+            .iter()
+            .filter(|s| s.source == source)
+            .find_map(|s| s.commit.as_deref())
+    }
+
+    pub fn has_source(&self, source: &str) -> bool {
+        self.skills.iter().any(|s| s.source == source)
+    }
+
+    fn upsert(&mut self, skill: LockedSkill) {
+        match self
+            .skills
+            .iter_mut()
+            .find(|s| s.source == skill.source && s.name == skill.name)
+        {
+            Some(existing) => *existing = skill,
+            None => self.skills.push(skill),
+        }
+    }
+}
+
+/// Records every skill in `report`. Agents that got the same skill share one
+/// entry, so it's keyed by source and name rather than by install location.
+pub fn record_install(report: &InstallReport, project: Option<&Path>) -> Result<()> {
+    let _lock = util::lock(&lock_path(project)?.with_file_name(WRITE_LOCK_FILE), true)?;
+    let mut lock = Lockfile::load(project)?;
+    for skill in &report.installed {
+        let digest = match (&skill.digest, &skill.path) {
+            (Some(digest), _) => digest.clone(),
+            (None, Some(path)) => digest::dir_digest(Path::new(path))?,
+            (None, None) => continue,
+        };
+        lock.upsert(LockedSkill {
+            name: skill.name.clone(),
+            source: report.source.clone(),
+            reference: report.reference.clone(),
+            commit: report.commit.clone(),
+            subpath: skill.subpath.clone(),
+            digest,
+        });
+    }
+    lock.save(project)
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const SKILL_FILE: &str = "SKILL.md";
+
+/// Typed view of the YAML frontmatter at the top of a SKILL.md file.
+///
+/// Only `name` and `description` are required by the skills spec; everything
+/// else is optional and unknown keys are kept in `extra` so nothing is lost.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkillManifest {
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub name: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub version: Option<String>,
+    #[serde(default, deserialize_with = "string_or_list")]
+    pub tags: Vec<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub author: Option<String>,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub license: Option<String>,
+    #[serde(
+        default,
+        rename = "allowed-tools",
+        alias = "allowed_tools",
+        deserialize_with = "string_or_list"
+    )]
+    pub allowed_tools: Vec<String>,
+    /// JSON Schema for `skill.config.json`: a path relative to the skill,
+    /// or the schema itself
+    #[serde(default, rename = "config-schema", alias = "config_schema")]
+    pub config_schema: Option<Value>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(std::io::Error),
+    MissingFrontmatter,
+    UnterminatedFrontmatter,
+    InvalidYaml(String),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(e) => write!(f, "Failed to read {}: {}", SKILL_FILE, e),
+            ManifestError::MissingFrontmatter => {
+                write!(f, "{} has no '---' frontmatter block", SKILL_FILE)
+            }
+            ManifestError::UnterminatedFrontmatter => {
+                write!(f, "{} frontmatter is missing its closing '---'", SKILL_FILE)
+            }
+            ManifestError::InvalidYaml(e) => write!(f, "Invalid {} frontmatter: {}", SKILL_FILE, e),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl SkillManifest {
+    /// Parses the frontmatter of a SKILL.md document.
+    pub fn parse(content: &str) -> Result<Self, ManifestError> {
+        let yaml = extract_frontmatter(content)?;
+        if yaml.trim().is_empty() {
+            return Ok(SkillManifest::default());
+        }
+        serde_yaml::from_str(yaml).map_err(|e| ManifestError::InvalidYaml(e.to_string()))
+    }
+
+    /// Reads and parses `<skill_dir>/SKILL.md`.
+    pub fn load(skill_dir: &Path) -> Result<Self, ManifestError> {
+        let content = fs::read_to_string(skill_dir.join(SKILL_FILE)).map_err(ManifestError::Io)?;
+        Self::parse(&content)
+    }
+
+    /// Like [`SkillManifest::load`], but never fails: a broken manifest yields
+    /// an empty one plus the error message so callers can surface it.
+    pub fn load_reported(skill_dir: &Path) -> (Self, Option<String>) {
+        match Self::load(skill_dir) {
+            Ok(manifest) => (manifest, None),
+            Err(e) => (SkillManifest::default(), Some(e.to_string())),
+        }
+    }
+
+    /// Description shown in listings. Left empty when the manifest failed to
+    /// parse so the UI shows the error instead of a placeholder.
+    pub fn display_description(&self, has_error: bool) -> String {
+        match &self.description {
+            Some(description) => description.clone(),
+            None if has_error => String::new(),
+            None => "No description".to_string(),
+        }
+    }
+}
+
+/// Returns the text between the opening and closing `---` lines.
+fn extract_frontmatter(content: &str) -> Result<&str, ManifestError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut lines = content.split_inclusive('\n');
+
+    match lines.next() {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return Err(ManifestError::MissingFrontmatter),
+    }
+
+    let start = content.find('\n').map(|i| i + 1).unwrap_or(content.len());
+    let mut offset = start;
+    for line in lines {
+        if line.trim_end() == "---" {
+            return Ok(&content[start..offset]);
+        }
+        offset += line.len();
+    }
+
+    Err(ManifestError::UnterminatedFrontmatter)
+}
+
+fn value_to_string(value: Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+// Accepts any YAML scalar so `version: 1.0` doesn't fail to parse.
+fn scalar_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(value_to_string(value).filter(|s| !s.is_empty()))
+}
+
+// Accepts either a YAML list or a comma (or, failing that, space) separated string.
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    let items = match value {
+        Value::Sequence(seq) => seq.into_iter().filter_map(value_to_string).collect(),
+        Value::String(s) if s.contains(',') => {
+            s.split(',').map(|item| item.trim().to_string()).collect()
+        }
+        Value::String(s) => s.split_whitespace().map(str::to_string).collect(),
+        Value::Null => Vec::new(),
+        other => value_to_string(other).into_iter().collect(),
+    };
+    Ok(items.into_iter().filter(|s| !s.is_empty()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folded_description() {
+        let manifest = SkillManifest::parse(
+            "---\nname: pdf\ndescription: >\n  Reads PDFs\n  and fills forms.\n---\n# PDF\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("pdf"));
+        assert_eq!(
+            manifest.description.as_deref(),
+            Some("Reads PDFs and fills forms.")
+        );
+    }
+
+    #[test]
+    fn literal_description_keeps_lines() {
+        let manifest =
+            SkillManifest::parse("---\ndescription: |\n  First line\n  Second line\n---\n")
+                .unwrap();
+        assert_eq!(
+            manifest.description.as_deref(),
+            Some("First line\nSecond line")
+        );
+    }
+
+    #[test]
+    fn quoted_values() {
+        let manifest = SkillManifest::parse(
+            "---\nname: \"docx: writer\"\ndescription: 'It''s --- fine'\nversion: \"1.0\"\n---\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("docx: writer"));
+        assert_eq!(manifest.description.as_deref(), Some("It's --- fine"));
+        assert_eq!(manifest.version.as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let manifest = SkillManifest::parse(
+            "---\r\nname: xlsx\r\ndescription: Spreadsheets\r\ntags: [a, b]\r\n---\r\nBody\r\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("xlsx"));
+        assert_eq!(manifest.description.as_deref(), Some("Spreadsheets"));
+        assert_eq!(manifest.tags, ["a", "b"]);
+    }
+
+    #[test]
+    fn unquoted_scalars_and_lists() {
+        let manifest = SkillManifest::parse(
+            "\u{feff}---\nversion: 1.0\ntags: one, two\nallowed-tools: Read Write\nextra-key: kept\n---\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.version.as_deref(), Some("1.0"));
+        assert_eq!(manifest.tags, ["one", "two"]);
+        assert_eq!(manifest.allowed_tools, ["Read", "Write"]);
+        assert!(manifest.extra.contains_key("extra-key"));
+    }
+
+    #[test]
+    fn frontmatter_errors() {
+        assert!(matches!(
+            SkillManifest::parse("# No frontmatter\n"),
+            Err(ManifestError::MissingFrontmatter)
+        ));
+        assert!(matches!(
+            SkillManifest::parse("---\nname: open\n"),
+            Err(ManifestError::UnterminatedFrontmatter)
+        ));
+        assert!(matches!(
+            SkillManifest::parse("---\nname: [unclosed\n---\n"),
+            Err(ManifestError::InvalidYaml(_))
+        ));
+    }
+}
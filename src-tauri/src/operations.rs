@@ -0,0 +1,529 @@
+//! Progress reporting and logs for long-running operations.
+//!
+//! Every install/remove/update gets an [`Operation`] that records its output
+//! and forwards progress to a [`ProgressSink`] (the Tauri event bus in the app).
+//! Logs of recent operations are kept in memory so they can be reviewed later.
+//!
+//! Running operations can be cancelled by id or time out. Either way their
+//! child processes are killed and half-written skill directories removed.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, SkillsError};
+use crate::util::now_secs;
+
+/// Event name the frontend listens on.
+pub const PROGRESS_EVENT: &str = "operation://progress";
+
+// Only the most recent operations are kept around
+const MAX_OPERATIONS: usize = 50;
+// How often a running child process is checked for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    Started,
+    Resolving,
+    Fetching,
+    Installing,
+    Removing,
+    Updating,
+    Finished,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+/// Why an operation was stopped before it completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupted {
+    Cancelled,
+    TimedOut(Duration),
+}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interrupted::Cancelled => write!(f, "Operation cancelled"),
+            Interrupted::TimedOut(limit) => {
+                write!(f, "TimedOut: operation exceeded {}s", limit.as_secs())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ProgressEvent {
+    pub operation_id: String,
+    pub phase: Phase,
+    pub percent: u8,
+    pub line: Option<String>,
+}
+
+pub trait ProgressSink: Send + Sync {
+    fn emit(&self, event: &ProgressEvent);
+}
+
+/// For callers with nowhere to send progress, like the CLI.
+pub struct NullSink;
+
+impl ProgressSink for NullSink {
+    fn emit(&self, _event: &ProgressEvent) {}
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OperationLog {
+    pub id: String,
+    pub kind: String,
+    pub phase: Phase,
+    pub percent: u8,
+    pub lines: Vec<String>,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+fn operations() -> &'static Mutex<VecDeque<OperationLog>> {
+    static OPERATIONS: OnceLock<Mutex<VecDeque<OperationLog>>> = OnceLock::new();
+    OPERATIONS.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+/// Shared state used to stop a running operation.
+#[derive(Default)]
+struct Control {
+    cancelled: AtomicBool,
+    deadline: Mutex<Option<(Instant, Duration)>>,
+    children: Mutex<Vec<u32>>,
+    // Directories being written; removed if the operation doesn't get to finish them
+    partial: Mutex<Vec<PathBuf>>,
+}
+
+impl Control {
+    fn interrupted(&self) -> Option<Interrupted> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Some(Interrupted::Cancelled);
+        }
+        let deadline = *self.deadline.lock().unwrap_or_else(|e| e.into_inner());
+        match deadline {
+            Some((at, limit)) if Instant::now() >= at => Some(Interrupted::TimedOut(limit)),
+            _ => None,
+        }
+    }
+
+    fn kill_children(&self) {
+        let children = self.children.lock().unwrap_or_else(|e| e.into_inner());
+        for pid in children.iter() {
+            kill_tree(*pid);
+        }
+    }
+}
+
+fn running() -> &'static Mutex<HashMap<String, Arc<Control>>> {
+    static RUNNING: OnceLock<Mutex<HashMap<String, Arc<Control>>>> = OnceLock::new();
+    RUNNING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Kills a child and everything it spawned (npx runs node which runs git...).
+fn kill_tree(pid: u32) {
+    #[cfg(unix)]
+    let mut cmd = {
+        // Children are started in their own process group, see `run_command`
+        let mut cmd = Command::new("kill");
+        cmd.args(["-KILL", "--", &format!("-{}", pid)]);
+        cmd
+    };
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/PID", &pid.to_string(), "/T", "/F"]);
+        cmd
+    };
+    let _ = cmd.stdout(Stdio::null()).stderr(Stdio::null()).status();
+}
+
+fn remove_partial(path: &Path) {
+    let result = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => return,
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to clean up {}: {}", path.display(), e);
+    }
+}
+
+/// Stops a running operation: kills its processes and lets it fail as cancelled.
+pub fn cancel_operation(id: &str) -> Result<()> {
+    let control = running()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(id)
+        .cloned()
+        .ok_or_else(|| SkillsError::InvalidInput(format!("Operation {} is not running", id)))?;
+    control.cancelled.store(true, Ordering::SeqCst);
+    control.kill_children();
+    Ok(())
+}
+
+fn new_operation_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("op-{:x}-{}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+fn with_log<R>(id: &str, f: impl FnOnce(&mut OperationLog) -> R) -> Option<R> {
+    let mut ops = operations().lock().unwrap_or_else(|e| e.into_inner());
+    ops.iter_mut().find(|op| op.id == id).map(f)
+}
+
+pub fn get_operation_log(id: &str) -> Option<OperationLog> {
+    with_log(id, |log| log.clone())
+}
+
+/// Recent operations, newest first, without their output.
+pub fn list_operations() -> Vec<OperationLog> {
+    let ops = operations().lock().unwrap_or_else(|e| e.into_inner());
+    ops.iter()
+        .rev()
+        .map(|op| OperationLog {
+            lines: Vec::new(),
+            ..op.clone()
+        })
+        .collect()
+}
+
+/// Handle to a running operation. Cheap to clone.
+#[derive(Clone)]
+pub struct Operation {
+    id: String,
+    tag: String,
+    sink: Arc<dyn ProgressSink>,
+    control: Arc<Control>,
+    // Maps this handle's 0-100 onto a slice of the overall progress
+    range: (u8, u8),
+}
+
+impl Operation {
+    /// Registers a new operation. `kind` is also used as the console log tag.
+    pub fn start(kind: &str, id: Option<String>, sink: Arc<dyn ProgressSink>) -> Self {
+        let id = id
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(new_operation_id);
+        {
+            let mut ops = operations().lock().unwrap_or_else(|e| e.into_inner());
+            ops.retain(|op| op.id != id);
+            ops.push_back(OperationLog {
+                id: id.clone(),
+                kind: kind.to_string(),
+                phase: Phase::Started,
+                percent: 0,
+                lines: Vec::new(),
+                started_at: now_secs(),
+                finished_at: None,
+            });
+            while ops.len() > MAX_OPERATIONS {
+                ops.pop_front();
+            }
+        }
+
+        let control = Arc::new(Control::default());
+        running()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(id.clone(), control.clone());
+
+        let op = Operation {
+            id,
+            tag: kind.to_uppercase(),
+            sink,
+            control,
+            range: (0, 100),
+        };
+        op.emit(None);
+        op
+    }
+
+    /// Fails the operation with [`Interrupted::TimedOut`] once `limit` has passed.
+    pub fn with_timeout(self, limit: Option<Duration>) -> Self {
+        if let Some(limit) = limit {
+            *self
+                .control
+                .deadline
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some((Instant::now() + limit, limit));
+        }
+        self
+    }
+
+    pub fn interrupted(&self) -> Option<Interrupted> {
+        self.control.interrupted()
+    }
+
+    /// Time left before the operation times out, if it has a timeout.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.control
+            .deadline
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .map(|(at, _)| at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns an error if the operation was cancelled or has timed out.
+    /// Long loops call this between steps.
+    pub fn check(&self) -> Result<()> {
+        match self.interrupted() {
+            Some(reason) => Err(reason.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Marks `path` as being written. Unless [`Operation::complete_write`] is
+    /// called, it is removed when the operation finishes.
+    pub fn begin_write(&self, path: &Path) {
+        self.control
+            .partial
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(path.to_path_buf());
+    }
+
+    pub fn complete_write(&self, path: &Path) {
+        self.control
+            .partial
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|p| p != path);
+    }
+
+    /// Converts a percentage of this handle's range into overall progress.
+    fn overall(&self, percent: u8) -> u8 {
+        let (from, to) = self.range;
+        from + ((to - from) as u32 * percent.min(100) as u32 / 100) as u8
+    }
+
+    /// A handle whose 0-100% covers `start..end` of this one, for sub-steps.
+    pub fn scoped(&self, start: u8, end: u8) -> Operation {
+        Operation {
+            range: (self.overall(start), self.overall(end)),
+            ..self.clone()
+        }
+    }
+
+    fn emit(&self, line: Option<String>) {
+        let state = with_log(&self.id, |log| (log.phase, log.percent));
+        let (phase, percent) = state.unwrap_or((Phase::Started, 0));
+        self.sink.emit(&ProgressEvent {
+            operation_id: self.id.clone(),
+            phase,
+            percent,
+            line,
+        });
+    }
+
+    pub fn set_phase(&self, phase: Phase, percent: u8) {
+        let overall = self.overall(percent);
+        with_log(&self.id, |log| {
+            log.phase = phase;
+            log.percent = overall;
+        });
+        self.emit(None);
+    }
+
+    pub fn log(&self, line: impl Into<String>) {
+        let line = line.into();
+        eprintln!("[{}] {}", self.tag, line);
+        with_log(&self.id, |log| log.lines.push(line.clone()));
+        self.emit(Some(line));
+    }
+
+    /// Marks the operation finished or failed depending on `result`.
+    pub fn finish<T>(&self, result: &Result<T>) {
+        running()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+
+        let partial: Vec<PathBuf> = self
+            .control
+            .partial
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect();
+        for path in partial {
+            self.log(format!("Removing partially written {}", path.display()));
+            remove_partial(&path);
+        }
+
+        let phase = match result {
+            Ok(_) => Phase::Finished,
+            Err(e) => {
+                self.log(format!("ERROR: {}", e));
+                match e {
+                    SkillsError::Cancelled => Phase::Cancelled,
+                    SkillsError::TimedOut { .. } => Phase::TimedOut,
+                    _ => Phase::Failed,
+                }
+            }
+        };
+        with_log(&self.id, |log| {
+            log.phase = phase;
+            log.percent = 100;
+            log.finished_at = Some(now_secs());
+        });
+        self.emit(None);
+    }
+
+    /// Runs `cmd`, streaming its stdout and stderr into the log line by line.
+    /// The process is killed if the operation is cancelled or times out.
+    pub fn run_command(&self, cmd: &mut Command, stdin: Option<&[u8]>) -> Result<CommandOutput> {
+        self.check()?;
+        let program = cmd.get_program().to_string_lossy().to_string();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            // Own process group so the whole tree can be killed at once
+            cmd.process_group(0);
+        }
+
+        let mut child = cmd
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| SkillsError::spawn(&program, e))?;
+
+        let pid = child.id();
+        self.log(format!("Spawned {} (PID {})", program, pid));
+        self.control
+            .children
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(pid);
+
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            let _ = pipe.write_all(input);
+        }
+
+        let stdout = child.stdout.take().map(|pipe| self.stream_lines(pipe));
+        let stderr = child.stderr.take().map(|pipe| self.stream_lines(pipe));
+
+        let waited = self.wait_child(&mut child);
+        self.control
+            .children
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|p| *p != pid);
+        let status = waited?;
+
+        let collect = |handle: Option<thread::JoinHandle<String>>| {
+            handle.and_then(|h| h.join().ok()).unwrap_or_default()
+        };
+
+        Ok(CommandOutput {
+            status,
+            stdout: collect(stdout),
+            stderr: collect(stderr),
+        })
+    }
+
+    fn wait_child(&self, child: &mut std::process::Child) -> Result<ExitStatus> {
+        loop {
+            let exited = child.try_wait().map_err(|e| SkillsError::Io {
+                path: None,
+                message: format!("Failed to wait for PID {}: {}", child.id(), e),
+            })?;
+            // Once reaped, the PID may belong to another process already
+            if let Some(status) = exited {
+                return Ok(status);
+            }
+            if let Some(reason) = self.interrupted() {
+                self.log(format!("{}, killing PID {}", reason, child.id()));
+                kill_tree(child.id());
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(reason.into());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn stream_lines(&self, pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+        let op = self.clone();
+        thread::spawn(move || {
+            let mut collected = String::new();
+            for line in BufReader::new(pipe)
+                .lines()
+                .map_while(std::result::Result::ok)
+            {
+                op.log(line.clone());
+                collected.push_str(&line);
+                collected.push('\n');
+            }
+            collected
+        })
+    }
+}
+
+pub struct CommandOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn operation() -> Operation {
+        Operation::start("test", None, Arc::new(NullSink))
+    }
+
+    #[test]
+    fn exited_child_wins_over_cancel() {
+        let op = operation();
+        let mut child = Command::new("true").spawn().unwrap();
+        // Let it exit before the operation is cancelled
+        thread::sleep(Duration::from_millis(300));
+        cancel_operation(&op.id).unwrap();
+        assert!(op.wait_child(&mut child).unwrap().success());
+    }
+
+    #[test]
+    fn cancel_kills_a_running_child() {
+        let op = operation();
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        let op2 = op.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            cancel_operation(&op2.id).unwrap();
+        });
+        let started = Instant::now();
+        let result = op.run_command(&mut cmd, None);
+        canceller.join().unwrap();
+        assert!(
+            matches!(result, Err(SkillsError::Cancelled)),
+            "{:?}",
+            result.err()
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Result, SkillsError};
+
+const APP_DIR_NAME: &str = "skills-manager";
+/// Overrides [`data_dir`], e.g. for portable installs or tests.
+pub const DATA_DIR_ENV: &str = "SKILLS_MANAGER_DATA_DIR";
+
+// Directory for user-editable settings such as agents.json
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME))
+}
+
+// Directory for state the app manages itself, such as skill_sources.json
+pub fn data_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR_NAME))
+        .ok_or_else(|| SkillsError::Other("Could not find data directory".to_string()))
+}
+
+pub fn home_dir() -> Result<PathBuf> {
+    dirs::home_dir().ok_or_else(|| SkillsError::Other("Could not find home directory".to_string()))
+}
+
+// Ensure the parent directory of `path` exists
+pub fn ensure_parent(path: &std::path::Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| SkillsError::io(parent, e))?;
+    }
+    Ok(())
+}
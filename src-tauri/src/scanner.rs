@@ -0,0 +1,322 @@
+//! Skill directory scanning.
+//!
+//! Directories are scanned in parallel. What is read from a skill (its
+//! manifest and git HEAD) is cached for the life of the process and reused
+//! while the skill directory, its SKILL.md and HEAD are unchanged, so
+//! repeated scans only stat files. A content digest is reused while no file
+//! in the skill is newer, and none was added or removed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use rayon::prelude::*;
+use walkdir::WalkDir;
+
+use crate::digest;
+use crate::git;
+use crate::manifest::SkillManifest;
+use crate::scope::SkillScope;
+use crate::skills::Skill;
+use crate::source_manager::SourceIndex;
+
+/// One agent's skills directory to scan.
+#[derive(Debug, Clone)]
+pub struct ScanDir {
+    pub agent: String,
+    pub dir: PathBuf,
+    pub scope: SkillScope,
+}
+
+/// Changes whenever a cached entry would be stale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    dir: Option<SystemTime>,
+    manifest: (Option<SystemTime>, u64),
+    head: Option<String>,
+}
+
+/// Changes whenever a file under a skill is added, removed or modified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TreeStamp {
+    newest: Option<SystemTime>,
+    entries: u64,
+    bytes: u64,
+}
+
+/// How often skills were read rather than taken from the cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanStats {
+    /// Manifests parsed
+    pub parsed: usize,
+    /// Content digests computed
+    pub digested: usize,
+}
+
+static PARSED: AtomicUsize = AtomicUsize::new(0);
+static DIGESTED: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts since the process started.
+pub fn stats() -> ScanStats {
+    ScanStats {
+        parsed: PARSED.load(Ordering::Relaxed),
+        digested: DIGESTED.load(Ordering::Relaxed),
+    }
+}
+
+/// The parts of a [`Skill`] read from disk.
+#[derive(Debug, Clone)]
+struct Parsed {
+    fingerprint: Fingerprint,
+    manifest: SkillManifest,
+    manifest_error: Option<String>,
+    head: Option<git::Head>,
+    /// Content digest, computed the first time provenance needs it, with
+    /// the state of the tree it was computed from
+    digest: Option<(TreeStamp, String)>,
+}
+
+fn cache() -> &'static Mutex<HashMap<PathBuf, Parsed>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Parsed>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Forgets everything cached for skills at or below `path`.
+pub fn invalidate(path: &Path) {
+    cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|cached, _| !cached.starts_with(path));
+}
+
+/// Forgets every cached skill, so the next scan reads everything again.
+pub fn clear() {
+    cache().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Scans `dirs` in parallel. Skills come back in the order of `dirs`.
+pub fn scan(dirs: &[ScanDir], sources: &Option<SourceIndex>) -> Vec<Skill> {
+    dirs.par_iter()
+        .flat_map_iter(|dir| scan_dir(dir, sources))
+        .collect()
+}
+
+fn scan_dir(dir: &ScanDir, sources: &Option<SourceIndex>) -> Vec<Skill> {
+    let Ok(entries) = fs::read_dir(&dir.dir) else {
+        return Vec::new();
+    };
+    let paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+
+    // Drop skills that were removed since the last scan
+    cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|cached, _| cached.parent() != Some(&dir.dir) || paths.contains(cached));
+
+    paths
+        .par_iter()
+        .filter_map(|path| scan_skill(dir, path, sources))
+        .collect()
+}
+
+/// Reads the skill at `path` in `dir`; `None` if it isn't one.
+pub fn scan_skill(dir: &ScanDir, path: &Path, sources: &Option<SourceIndex>) -> Option<Skill> {
+    if !path.is_dir() {
+        return None;
+    }
+    let manifest_meta = fs::metadata(path.join("SKILL.md")).ok()?;
+
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    let skill_id = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let is_repo = !is_symlink && path.join(".git").exists();
+
+    let fingerprint = Fingerprint {
+        dir: fs::metadata(path).and_then(|m| m.modified()).ok(),
+        manifest: (manifest_meta.modified().ok(), manifest_meta.len()),
+        head: if is_repo {
+            git::head_fingerprint(path)
+        } else {
+            None
+        },
+    };
+    // Repositories whose HEAD can't be fingerprinted (e.g. a `.git` file)
+    // are read every time
+    let cacheable = !is_repo || fingerprint.head.is_some();
+
+    let cached = cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(path)
+        .filter(|parsed| cacheable && parsed.fingerprint == fingerprint)
+        .cloned();
+    let parsed = match cached {
+        Some(parsed) => parsed,
+        None => {
+            PARSED.fetch_add(1, Ordering::Relaxed);
+            let (manifest, manifest_error) = SkillManifest::load_reported(path);
+            let head = if is_repo { git::head(path).ok() } else { None };
+            let parsed = Parsed {
+                fingerprint,
+                manifest,
+                manifest_error,
+                head,
+                digest: None,
+            };
+            if cacheable {
+                cache()
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(path.to_path_buf(), parsed.clone());
+            }
+            parsed
+        }
+    };
+
+    let agent_name = dir.agent.as_str();
+    // Try to get source from saved sources first, then fallback to .git/config
+    let source = sources
+        .as_ref()
+        .and_then(|index| index.lookup_with(&dir.scope, agent_name, path, || content_digest(path)))
+        .map(|r| r.source.clone())
+        .or_else(|| config_remote(path));
+    let recorded = sources
+        .as_ref()
+        .and_then(|index| index.entry(&dir.scope, agent_name, path));
+
+    let Parsed {
+        manifest,
+        manifest_error,
+        head,
+        ..
+    } = parsed;
+    let description = manifest.display_description(manifest_error.is_some());
+    let name = manifest.name.unwrap_or_else(|| skill_id.clone());
+    let mut tags = manifest.tags;
+    if !tags.iter().any(|t| t == agent_name) {
+        tags.push(agent_name.to_string());
+    }
+
+    // Plain copies have no commit; show the digest taken at install
+    let local_hash = head
+        .as_ref()
+        .map(git::Head::short)
+        .or_else(|| recorded.and_then(|entry| entry.content_hash.clone()));
+
+    Some(Skill {
+        id: skill_id,
+        name,
+        description,
+        author: manifest.author.unwrap_or_else(|| "local".to_string()),
+        stars: 0,
+        tags,
+        installed: true,
+        version: manifest.version,
+        downloads: None,
+        agent: agent_name.to_string(),
+        scope: dir.scope.clone(),
+        path: path.to_string_lossy().to_string(),
+        is_symlink,
+        source,
+        has_update: false,
+        local_hash,
+        remote_hash: None,
+        last_updated: head.and_then(|head| head.date),
+        license: manifest.license,
+        allowed_tools: manifest.allowed_tools,
+        manifest_error,
+    })
+}
+
+/// Stats everything [`digest::dir_digest`] would read under `path`.
+fn tree_stamp(path: &Path) -> Option<TreeStamp> {
+    let mut stamp = TreeStamp {
+        newest: None,
+        entries: 0,
+        bytes: 0,
+    };
+    let walker = WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git");
+    for entry in walker {
+        let meta = entry.ok()?.metadata().ok()?;
+        stamp.newest = stamp.newest.max(meta.modified().ok());
+        stamp.entries += 1;
+        if meta.is_file() {
+            stamp.bytes += meta.len();
+        }
+    }
+    Some(stamp)
+}
+
+/// [`digest::dir_digest`] of a skill, cached alongside what was parsed.
+fn content_digest(path: &Path) -> Option<String> {
+    let stamp = tree_stamp(path)?;
+    let cached = cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(path)
+        .and_then(|parsed| parsed.digest.clone())
+        .filter(|(cached, _)| *cached == stamp);
+    if let Some((_, hash)) = cached {
+        return Some(hash);
+    }
+    DIGESTED.fetch_add(1, Ordering::Relaxed);
+    let hash = digest::dir_digest(path).ok()?;
+    if let Some(parsed) = cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_mut(path)
+    {
+        parsed.digest = Some((stamp, hash.clone()));
+    }
+    Some(hash)
+}
+
+/// The first remote URL in `.git/config`.
+fn config_remote(path: &Path) -> Option<String> {
+    let config = fs::read_to_string(path.join(".git").join("config")).ok()?;
+    config
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("url = ").map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_follows_nested_edits() {
+        let root = tempfile::tempdir().unwrap();
+        let skill = root.path().join("demo");
+        fs::create_dir_all(skill.join("scripts")).unwrap();
+        fs::write(skill.join("SKILL.md"), "---\nname: demo\n---\n").unwrap();
+        fs::write(skill.join("scripts").join("run.sh"), "echo one\n").unwrap();
+        let dir = ScanDir {
+            agent: "claude".to_string(),
+            dir: root.path().to_path_buf(),
+            scope: SkillScope::for_home_agent("claude"),
+        };
+        scan_skill(&dir, &skill, &None).unwrap();
+
+        let first = content_digest(&skill).unwrap();
+        let digested = stats().digested;
+        assert_eq!(content_digest(&skill).unwrap(), first);
+        assert_eq!(stats().digested, digested);
+
+        // Leaves the skill directory and SKILL.md untouched
+        fs::write(skill.join("scripts").join("run.sh"), "echo two, longer\n").unwrap();
+        scan_skill(&dir, &skill, &None).unwrap();
+        let second = content_digest(&skill).unwrap();
+        assert_ne!(second, first);
+        assert_eq!(second, digest::dir_digest(&skill).unwrap());
+    }
+}
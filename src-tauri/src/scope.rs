@@ -0,0 +1,58 @@
+use std::path::{Path, PathBuf};
+
+use crate::agents::{AgentDefinition, GLOBAL_AGENT};
+use crate::error::{Result, SkillsError};
+use crate::paths;
+
+/// Where an installed skill lives.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SkillScope {
+    /// The shared `~/.agents/skills` store
+    Global,
+    /// An agent's directory under the home directory
+    #[default]
+    User,
+    /// An agent's directory inside a project checkout
+    Project { path: String },
+}
+
+impl SkillScope {
+    pub fn for_home_agent(agent_id: &str) -> Self {
+        if agent_id == GLOBAL_AGENT {
+            SkillScope::Global
+        } else {
+            SkillScope::User
+        }
+    }
+
+    pub fn project(root: &Path) -> Self {
+        SkillScope::Project {
+            path: root.to_string_lossy().to_string(),
+        }
+    }
+
+    pub fn project_path(&self) -> Option<&str> {
+        match self {
+            SkillScope::Project { path } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// Validates a project target coming from the frontend.
+pub fn project_root(project: &str) -> Result<PathBuf> {
+    let root = PathBuf::from(project);
+    if !root.is_dir() {
+        return Err(SkillsError::NotFound { path: root });
+    }
+    root.canonicalize().map_err(|e| SkillsError::io(&root, e))
+}
+
+/// Skills directory for `agent`, inside `project` when given, otherwise under home.
+pub fn skills_dir(agent: &AgentDefinition, project: Option<&str>) -> Result<PathBuf> {
+    match project {
+        Some(project) => Ok(agent.project_skills_dir(&project_root(project)?)),
+        None => Ok(agent.skills_dir(&paths::home_dir()?)),
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{Result, SkillsError};
+use crate::paths;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+// Seconds, by operation kind. `0` disables the timeout.
+const DEFAULT_TIMEOUTS: &[(&str, u64)] = &[
+    ("install", 600),
+    ("import", 3600),
+    ("remove_skills", 120),
+    ("remove_global", 120),
+    ("update", 300),
+    ("check_updates", 120),
+    ("sync", 3600),
+];
+
+/// Days a removed skill stays in the trash.
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
+/// Saved versions kept of each skill config.
+const DEFAULT_CONFIG_HISTORY_LIMIT: usize = 20;
+
+/// App settings stored in settings.json next to agents.json.
+#[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Settings {
+    /// Per-operation timeouts in seconds, overriding the defaults.
+    #[serde(default)]
+    pub timeouts: BTreeMap<String, u64>,
+    /// Days before trashed skills are purged; `0` keeps them until purged by hand.
+    #[serde(default)]
+    pub trash_retention_days: Option<u32>,
+    /// Versions of each skill config kept for rollback.
+    #[serde(default)]
+    pub config_history_limit: Option<usize>,
+}
+
+fn settings_file_path() -> Result<PathBuf> {
+    paths::config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .ok_or_else(|| SkillsError::Other("Could not find config directory".to_string()))
+}
+
+pub fn load() -> Result<Settings> {
+    let path = settings_file_path()?;
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+    serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))
+}
+
+fn save(settings: &Settings) -> Result<()> {
+    let path = settings_file_path()?;
+    paths::ensure_parent(&path)?;
+    let content =
+        serde_json::to_string_pretty(settings).map_err(|e| SkillsError::Other(e.to_string()))?;
+    fs::write(&path, content).map_err(|e| SkillsError::io(&path, e))
+}
+
+/// Effective timeouts for every known operation kind.
+pub fn timeouts() -> Result<BTreeMap<String, u64>> {
+    let mut timeouts: BTreeMap<String, u64> = DEFAULT_TIMEOUTS
+        .iter()
+        .map(|(kind, secs)| (kind.to_string(), *secs))
+        .collect();
+    timeouts.extend(load()?.timeouts);
+    Ok(timeouts)
+}
+
+pub fn timeout_for(kind: &str) -> Option<Duration> {
+    let secs = match load() {
+        Ok(settings) => settings.timeouts.get(kind).copied(),
+        Err(e) => {
+            eprintln!("Using default timeouts: {}", e);
+            None
+        }
+    }
+    .or_else(|| {
+        DEFAULT_TIMEOUTS
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, secs)| *secs)
+    })?;
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Sets the timeout for `kind`; `None` restores the default.
+pub fn set_timeout(kind: &str, seconds: Option<u64>) -> Result<()> {
+    let mut settings = load()?;
+    match seconds {
+        Some(secs) => settings.timeouts.insert(kind.to_string(), secs),
+        None => settings.timeouts.remove(kind),
+    };
+    save(&settings)
+}
+
+/// Effective trash retention in days; `0` means forever.
+pub fn trash_retention_days() -> u32 {
+    match load() {
+        Ok(settings) => settings.trash_retention_days,
+        Err(e) => {
+            eprintln!("Using default trash retention: {}", e);
+            None
+        }
+    }
+    .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+}
+
+/// Sets the trash retention; `None` restores the default.
+pub fn set_trash_retention_days(days: Option<u32>) -> Result<()> {
+    let mut settings = load()?;
+    settings.trash_retention_days = days;
+    save(&settings)
+}
+
+/// Effective number of versions kept of each skill config.
+pub fn config_history_limit() -> usize {
+    match load() {
+        Ok(settings) => settings.config_history_limit,
+        Err(e) => {
+            eprintln!("Using default config history limit: {}", e);
+            None
+        }
+    }
+    .unwrap_or(DEFAULT_CONFIG_HISTORY_LIMIT)
+}
@@ -0,0 +1,314 @@
+//! `skill.config.json` and the JSON Schema a skill may ship for it.
+//!
+//! The schema is either named or inlined by the `config-schema` frontmatter
+//! key, or sits next to SKILL.md as `config.schema.json`. Configs are checked
+//! against it before being saved, and `default` values from its `properties`
+//! fill in keys the config doesn't set.
+//!
+//! String properties marked `"x-secret": true` (or the standard
+//! `"writeOnly": true`) are kept out of the file: their values go to the
+//! [`vault`] and the config only holds a reference.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::config_history;
+use crate::error::{FieldError, Result, SkillsError};
+use crate::manifest::{SkillManifest, SKILL_FILE};
+use crate::vault::{self, Vault};
+
+pub const CONFIG_FILE: &str = "skill.config.json";
+pub const SCHEMA_FILE: &str = "config.schema.json";
+/// What exports show instead of a secret value.
+pub const REDACTED: &str = "<redacted>";
+
+#[derive(Debug, Clone)]
+pub struct Schema {
+    /// Where the schema came from: its file, or SKILL.md when inlined
+    pub path: PathBuf,
+    pub value: Value,
+}
+
+/// The config schema of the skill in `skill_dir`, if it has one.
+pub fn load_schema(skill_dir: &Path) -> Result<Option<Schema>> {
+    let declared = SkillManifest::load(skill_dir)
+        .ok()
+        .and_then(|manifest| manifest.config_schema);
+    let path = match declared {
+        Some(serde_yaml::Value::String(relative)) => schema_in(skill_dir, &relative)?,
+        Some(inline) => {
+            let path = skill_dir.join(SKILL_FILE);
+            let value =
+                serde_json::to_value(inline).map_err(|e| SkillsError::invalid_config(&path, e))?;
+            return Ok(Some(Schema { path, value }));
+        }
+        None => {
+            let path = skill_dir.join(SCHEMA_FILE);
+            if !path.is_file() {
+                return Ok(None);
+            }
+            path
+        }
+    };
+    let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+    let value =
+        serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))?;
+    Ok(Some(Schema { path, value }))
+}
+
+/// The schema file `relative` names, which must be inside the skill: a
+/// config editor shouldn't be able to read `/etc/passwd` or `../../x`.
+fn schema_in(skill_dir: &Path, relative: &str) -> Result<PathBuf> {
+    let path = skill_dir.join(relative);
+    let resolved = fs::canonicalize(&path).map_err(|e| SkillsError::io(&path, e))?;
+    let root = fs::canonicalize(skill_dir).map_err(|e| SkillsError::io(skill_dir, e))?;
+    if !resolved.starts_with(&root) {
+        return Err(SkillsError::invalid_config(
+            skill_dir.join(SKILL_FILE),
+            format!("config-schema {} is outside the skill", relative),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Sets every key of `config` the schema gives a `default` for but the
+/// config leaves out, following nested `properties`. Returns whether
+/// anything was added.
+pub fn apply_defaults(schema: &Value, config: &mut Value) -> bool {
+    let (Some(properties), Some(config)) = (
+        schema.get("properties").and_then(Value::as_object),
+        config.as_object_mut(),
+    ) else {
+        return false;
+    };
+    let mut changed = false;
+    for (key, property) in properties {
+        match config.get_mut(key) {
+            Some(value) => changed |= apply_defaults(property, value),
+            None => {
+                if let Some(default) = property.get("default") {
+                    config.insert(key.clone(), default.clone());
+                    changed = true;
+                } else {
+                    // Only create a nested object when it gets some defaults
+                    let mut nested = Value::Object(Map::new());
+                    if apply_defaults(property, &mut nested) {
+                        config.insert(key.clone(), nested);
+                        changed = true;
+                    }
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// Parses `config`, fills in defaults from `schema` and checks the result
+/// against it. Errors name the offending fields by JSON pointer.
+pub fn validate(config: &str, schema: Option<&Schema>, config_path: &Path) -> Result<Value> {
+    let mut value: Value =
+        serde_json::from_str(config).map_err(|e| SkillsError::InvalidSkillConfig {
+            path: config_path.to_path_buf(),
+            errors: vec![FieldError {
+                pointer: String::new(),
+                message: e.to_string(),
+            }],
+        })?;
+    let Some(schema) = schema else {
+        return Ok(value);
+    };
+    apply_defaults(&schema.value, &mut value);
+    // A stored secret is checked when it's entered, not against its reference
+    let stored: Vec<String> = secret_pointers(&schema.value)
+        .into_iter()
+        .filter(|pointer| {
+            value
+                .pointer(pointer)
+                .and_then(Value::as_str)
+                .and_then(vault::reference_id)
+                .is_some()
+        })
+        .collect();
+
+    let validator = jsonschema::validator_for(&schema.value)
+        .map_err(|e| SkillsError::invalid_config(&schema.path, e))?;
+    let errors: Vec<FieldError> = validator
+        .iter_errors(&value)
+        .filter(|e| !stored.iter().any(|p| p == e.instance_path().as_str()))
+        .map(|e| FieldError {
+            pointer: e.instance_path().as_str().to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(SkillsError::InvalidSkillConfig {
+            path: config_path.to_path_buf(),
+            errors,
+        });
+    }
+    Ok(value)
+}
+
+fn is_secret(property: &Value) -> bool {
+    ["x-secret", "writeOnly"]
+        .iter()
+        .any(|keyword| property.get(keyword) == Some(&Value::Bool(true)))
+}
+
+/// JSON pointers of the properties `schema` marks as secret.
+pub fn secret_pointers(schema: &Value) -> Vec<String> {
+    let mut pointers = Vec::new();
+    collect_secrets(schema, "", &mut pointers);
+    pointers
+}
+
+fn collect_secrets(schema: &Value, prefix: &str, pointers: &mut Vec<String>) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    for (key, property) in properties {
+        let pointer = format!("{}/{}", prefix, key.replace('~', "~0").replace('/', "~1"));
+        if is_secret(property) {
+            pointers.push(pointer);
+        } else {
+            collect_secrets(property, &pointer, pointers);
+        }
+    }
+}
+
+/// Moves the secret values of `config` into the vault, leaving references
+/// in their place. Vault entries of `config_path` that neither the new
+/// config, the one on disk nor any version in its history refers to are
+/// dropped. A reference to a secret the vault no longer has is an error.
+pub fn seal_secrets(schema: &Value, config: &mut Value, config_path: &Path) -> Result<()> {
+    let pointers = secret_pointers(schema);
+    if pointers.is_empty() {
+        return Ok(());
+    }
+    let mut vault = Vault::load()?;
+    let mut missing = Vec::new();
+    let mut changed = false;
+    for pointer in &pointers {
+        let Some(Value::String(secret)) = config.pointer_mut(pointer) else {
+            continue;
+        };
+        if let Some(id) = vault::reference_id(secret) {
+            if !vault.contains(id) {
+                missing.push(FieldError {
+                    pointer: pointer.clone(),
+                    message: format!("{} is no longer in the vault; enter it again", secret),
+                });
+            }
+            continue;
+        }
+        *secret = vault.put(config_path, pointer, secret)?;
+        changed = true;
+    }
+    if !missing.is_empty() {
+        return Err(SkillsError::InvalidSkillConfig {
+            path: config_path.to_path_buf(),
+            errors: missing,
+        });
+    }
+
+    let mut keep = Vec::new();
+    collect_references(config, &mut keep);
+    let saved = fs::read_to_string(config_path).into_iter().chain(
+        config_history::list(config_path)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| v.content),
+    );
+    for content in saved {
+        if let Ok(value) = serde_json::from_str::<Value>(&content) {
+            collect_references(&value, &mut keep);
+        }
+    }
+    let keep: Vec<&str> = keep.iter().map(String::as_str).collect();
+    changed |= vault.retain(config_path, &keep);
+    if changed {
+        vault.save()?;
+    }
+    Ok(())
+}
+
+/// Ids of every `vault:<id>` reference in `value`.
+fn collect_references(value: &Value, ids: &mut Vec<String>) {
+    match value {
+        Value::String(s) => ids.extend(vault::reference_id(s).map(str::to_string)),
+        Value::Array(items) => items.iter().for_each(|v| collect_references(v, ids)),
+        Value::Object(map) => map.values().for_each(|v| collect_references(v, ids)),
+        _ => {}
+    }
+}
+
+/// Replaces the secret values of `config`, stored or not, with [`REDACTED`].
+pub fn redact(schema: &Value, config: &mut Value) {
+    for pointer in secret_pointers(schema) {
+        if let Some(value) = config.pointer_mut(&pointer).filter(|v| !v.is_null()) {
+            *value = Value::String(REDACTED.to_string());
+        }
+    }
+}
+
+/// The config of the skill in `skill_dir` with its secrets redacted, for
+/// exports. `None` when the skill has no config.
+pub fn redacted_config(skill_dir: &Path) -> Result<Option<Value>> {
+    let path = skill_dir.join(CONFIG_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    let mut config: Value =
+        serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))?;
+    if let Some(schema) = load_schema(skill_dir)? {
+        redact(&schema.value, &mut config);
+    }
+    Ok(Some(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill_with_schema(dir: &Path, schema: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join(SKILL_FILE),
+            format!(
+                "---\nname: pdf\ndescription: Reads PDFs\nconfig-schema: {}\n---\n",
+                schema
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn loads_a_named_schema_inside_the_skill() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill = dir.path().join("pdf");
+        skill_with_schema(&skill, "schemas/config.json");
+        fs::create_dir_all(skill.join("schemas")).unwrap();
+        fs::write(skill.join("schemas/config.json"), r#"{"type":"object"}"#).unwrap();
+
+        let schema = load_schema(&skill).unwrap().unwrap();
+        assert_eq!(schema.value, serde_json::json!({ "type": "object" }));
+    }
+
+    #[test]
+    fn refuses_a_schema_outside_the_skill() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill = dir.path().join("pdf");
+        fs::write(dir.path().join("secret.json"), "{}").unwrap();
+        let absolute = dir.path().join("secret.json");
+
+        for named in ["../secret.json", &absolute.to_string_lossy()] {
+            skill_with_schema(&skill, named);
+            assert!(matches!(
+                load_schema(&skill),
+                Err(SkillsError::InvalidConfig { .. })
+            ));
+        }
+    }
+}
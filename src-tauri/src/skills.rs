@@ -0,0 +1,660 @@
+//! Skill management shared by the GUI commands and the CLI.
+//!
+//! Nothing here depends on Tauri; progress is reported through an
+//! [`Operation`] so each frontend can show it its own way.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agents::{AgentRegistry, GLOBAL_AGENT};
+use crate::config_history::{self, ConfigChange, ConfigVersion};
+use crate::error::{Result, SkillsError};
+use crate::git;
+use crate::installer::convert::Converted;
+use crate::installer::{
+    self, Backend, ImportEntry, ImportResult, InstallMode, InstallReport, InstallRequest,
+    RemoveReport, RemoveRequest,
+};
+use crate::links;
+use crate::lockfile;
+use crate::manifest::SkillManifest;
+use crate::operations::{Operation, Phase};
+use crate::paths;
+use crate::scanner::{self, ScanDir};
+use crate::scope::{self, SkillScope};
+use crate::skill_config;
+use crate::source_manager::{self, SourceIndex, SourceRecord};
+use crate::trash;
+use crate::workspaces;
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Skill {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub author: String,
+    pub stars: u32,
+    pub tags: Vec<String>,
+    pub installed: bool,
+    pub version: Option<String>,
+    pub downloads: Option<u32>,
+    pub agent: String,
+    #[serde(default)]
+    pub scope: SkillScope,
+    #[serde(default)]
+    pub path: String,
+    pub is_symlink: bool,
+    pub source: Option<String>,
+    pub has_update: bool,
+    pub local_hash: Option<String>,
+    pub remote_hash: Option<String>,
+    pub last_updated: Option<String>,
+    #[serde(default)]
+    pub license: Option<String>,
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    // Set when SKILL.md exists but its frontmatter could not be parsed
+    #[serde(default)]
+    pub manifest_error: Option<String>,
+}
+
+/// Every skill in the enabled agents' home directories and registered workspaces.
+pub fn local_skills() -> Result<Vec<Skill>> {
+    let dirs = local_scan_dirs()?;
+    Ok(scanner::scan(&dirs, &load_source_index()))
+}
+
+/// The directories [`local_skills`] scans.
+pub fn local_scan_dirs() -> Result<Vec<ScanDir>> {
+    let home_dir = paths::home_dir()?;
+    let registry = AgentRegistry::load()?;
+    let mut dirs = Vec::new();
+    // Several agents can share a directory, only report each directory once
+    let mut scanned = HashSet::new();
+
+    for agent in registry.enabled() {
+        let skills_dir = agent.skills_dir(&home_dir);
+        if scanned.insert(skills_dir.clone()) {
+            dirs.push(ScanDir {
+                agent: agent.id.clone(),
+                dir: skills_dir,
+                scope: SkillScope::for_home_agent(&agent.id),
+            });
+        }
+    }
+
+    for root in workspaces::list_workspaces()? {
+        for agent in registry.enabled() {
+            let skills_dir = agent.project_skills_dir(&root);
+            if scanned.insert(skills_dir.clone()) {
+                dirs.push(ScanDir {
+                    agent: agent.id.clone(),
+                    dir: skills_dir,
+                    scope: SkillScope::project(&root),
+                });
+            }
+        }
+    }
+
+    Ok(dirs)
+}
+
+/// Skills of every enabled agent in one place: the home directory, or
+/// `project` when given.
+pub fn skills_in(project: Option<&Path>) -> Result<Vec<Skill>> {
+    let home_dir = paths::home_dir()?;
+    let registry = AgentRegistry::load()?;
+    let sources = load_source_index();
+    let mut dirs = Vec::new();
+    let mut scanned = HashSet::new();
+
+    for agent in registry.enabled() {
+        let (skills_dir, scope) = match project {
+            Some(root) => (agent.project_skills_dir(root), SkillScope::project(root)),
+            None => (
+                agent.skills_dir(&home_dir),
+                SkillScope::for_home_agent(&agent.id),
+            ),
+        };
+        if scanned.insert(skills_dir.clone()) {
+            dirs.push(ScanDir {
+                agent: agent.id.clone(),
+                dir: skills_dir,
+                scope,
+            });
+        }
+    }
+    Ok(scanner::scan(&dirs, &sources))
+}
+
+// A broken provenance store shouldn't hide installed skills
+pub fn load_source_index() -> Option<SourceIndex> {
+    SourceIndex::load()
+        .map_err(|e| eprintln!("Failed to load skill sources: {}", e))
+        .ok()
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GlobalSkillInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub used_by: Vec<String>,
+    pub source: Option<String>,
+    pub manifest_error: Option<String>,
+}
+
+/// Skills in the shared store, with the agents linking to them.
+pub fn global_skills() -> Result<Vec<GlobalSkillInfo>> {
+    let home_dir = paths::home_dir()?;
+    let registry = AgentRegistry::load()?;
+    let global_skills_path = registry.require(GLOBAL_AGENT)?.skills_dir(&home_dir);
+    let sources = load_source_index();
+
+    if !global_skills_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut global_skills = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&global_skills_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let skill_id = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+
+                let skill_md_path = path.join("SKILL.md");
+
+                if skill_md_path.exists() {
+                    let (manifest, manifest_error) = SkillManifest::load_reported(&path);
+                    let description = manifest.display_description(manifest_error.is_some());
+                    let name = manifest.name.unwrap_or_else(|| skill_id.clone());
+
+                    // Agents whose entry is a link resolving to this copy
+                    let mut used_by = Vec::new();
+                    for dependent in links::dependents(&path).unwrap_or_default() {
+                        if !used_by.contains(&dependent.agent) {
+                            used_by.push(dependent.agent);
+                        }
+                    }
+
+                    global_skills.push(GlobalSkillInfo {
+                        id: skill_id,
+                        name,
+                        description,
+                        used_by,
+                        source: sources
+                            .as_ref()
+                            .and_then(|index| {
+                                index.lookup(&SkillScope::Global, GLOBAL_AGENT, &path)
+                            })
+                            .map(|r| r.source.clone()),
+                        manifest_error,
+                    });
+
+                    // Fall back to the git remote for the last added skill
+                    if let Some(last_skill) =
+                        global_skills.last_mut().filter(|s| s.source.is_none())
+                    {
+                        if path.join(".git").exists() {
+                            last_skill.source = git::remote_url(&path).ok().flatten();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(global_skills)
+}
+
+/// Installs `request` and records where each skill came from and, unless
+/// the install was frozen, what it resolved to in skills.lock.
+pub fn install(
+    request: &InstallRequest,
+    backend: Backend,
+    op: &Operation,
+) -> Result<InstallReport> {
+    let registry = AgentRegistry::load()?;
+    let report = installer::install(request, backend, &registry, op)?;
+    record_sources(&report, request.project.as_deref(), op);
+    if !request.frozen {
+        record_lock(&report, request.project.as_deref(), op);
+    }
+    Ok(report)
+}
+
+fn record_lock(report: &InstallReport, project: Option<&Path>, op: &Operation) {
+    if let Err(e) = lockfile::record_install(report, project) {
+        op.log(format!("Failed to update {}: {}", lockfile::LOCK_FILE, e));
+    }
+}
+
+fn record_sources(report: &InstallReport, project: Option<&Path>, op: &Operation) {
+    match source_manager::record_install(report, project) {
+        Ok(names) => op.log(format!(
+            "Saved source for {}: {}",
+            names.join(", "),
+            report.source
+        )),
+        Err(e) => op.log(format!("Failed to save sources: {}", e)),
+    }
+}
+
+/// Installs several sources; one failing doesn't stop the rest.
+pub fn import(
+    entries: &[ImportEntry],
+    mode: InstallMode,
+    project: Option<&Path>,
+    backend: Backend,
+    op: &Operation,
+) -> Result<Vec<ImportResult>> {
+    let registry = AgentRegistry::load()?;
+    let results = installer::import(entries, mode, project, backend, &registry, op);
+    for report in results.iter().filter_map(|r| r.report.as_ref()) {
+        record_sources(report, project, op);
+        record_lock(report, project, op);
+    }
+    Ok(results)
+}
+
+pub fn remove(request: &RemoveRequest, backend: Backend, op: &Operation) -> Result<RemoveReport> {
+    let registry = AgentRegistry::load()?;
+    installer::remove(request, backend, &registry, op)
+}
+
+pub fn remove_global(id: &str, backend: Backend, op: &Operation) -> Result<RemoveReport> {
+    let registry = AgentRegistry::load()?;
+    installer::remove_global(id, backend, &registry, op)
+}
+
+/// Switches an installed skill between a link into the global store and a
+/// standalone copy.
+pub fn convert_install_mode(
+    name: &str,
+    agent: &str,
+    mode: InstallMode,
+    project: Option<&Path>,
+    op: &Operation,
+) -> Result<Converted> {
+    let registry = AgentRegistry::load()?;
+    installer::convert::convert(name, agent, mode, project, &registry, op)
+}
+
+/// Moves a skill to the trash from each of `agents`, reporting per agent.
+/// A symlinked entry only loses the link. A real copy that other agents
+/// still link to is left alone unless `cascade` allows removing those
+/// links with it.
+pub fn uninstall(
+    id: &str,
+    agents: &[String],
+    project: Option<&str>,
+    cascade: bool,
+) -> Result<String> {
+    let registry = AgentRegistry::load()?;
+    let mut messages = Vec::new();
+    let project_root = project.map(scope::project_root).transpose()?;
+
+    let store_dir = scope::skills_dir(registry.require(GLOBAL_AGENT)?, project)?;
+    let removes_store = agents.iter().any(|a| a == GLOBAL_AGENT);
+    let mut targets = Vec::new();
+    for agent_name in agents {
+        match registry.get(agent_name) {
+            Some(agent) => {
+                let dir = scope::skills_dir(agent, project)?;
+                if !removes_store {
+                    installer::refuse_store_dir(agent_name, &dir, &store_dir)?;
+                }
+                targets.push((agent_name, dir.join(id)));
+            }
+            None => messages.push(format!("{}: Unknown agent", agent_name)),
+        }
+    }
+    // Fail before removing anything if a copy is still in use elsewhere
+    let removing: Vec<_> = targets.iter().map(|(_, path)| path.clone()).collect();
+    let mut cascaded = Vec::new();
+    for (_, path) in &targets {
+        cascaded.push(links::check_dependents(path, &removing, cascade)?);
+    }
+
+    for ((agent_name, skill_path), dependents) in targets.into_iter().zip(cascaded) {
+        let skill_scope = match &project_root {
+            Some(root) => SkillScope::project(root),
+            None => SkillScope::for_home_agent(agent_name),
+        };
+
+        // A dangling link is still there to remove
+        if fs::symlink_metadata(&skill_path).is_err() {
+            messages.push(format!(
+                "{}: Not found at {}",
+                agent_name,
+                skill_path.display()
+            ));
+            continue;
+        }
+
+        for dependent in dependents {
+            let link = Path::new(&dependent.path);
+            match trash::move_to_trash(link, &dependent.agent, &dependent.scope) {
+                Ok(entry) => messages.push(format!(
+                    "{}: Removed link {} ({})",
+                    dependent.agent, dependent.path, entry.id
+                )),
+                Err(e) => messages.push(format!("{}: Error ({})", dependent.agent, e)),
+            }
+        }
+
+        match trash::move_to_trash(&skill_path, agent_name, &skill_scope) {
+            Ok(entry) => match &entry.link_target {
+                Some(target) => messages.push(format!(
+                    "{}: Removed link to {} ({})",
+                    agent_name, target, entry.id
+                )),
+                None => messages.push(format!("{}: Moved to trash ({})", agent_name, entry.id)),
+            },
+            Err(e) => messages.push(format!("{}: Error ({})", agent_name, e)),
+        }
+    }
+
+    Ok(messages.join("\n"))
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SkillConfigResponse {
+    pub current_config: String,
+    pub documentation: Option<String>,
+    /// JSON Schema the config is validated against
+    pub schema: Option<serde_json::Value>,
+}
+
+pub fn skill_config(id: &str, agent: &str, project: Option<&str>) -> Result<SkillConfigResponse> {
+    let registry = AgentRegistry::load()?;
+    let skill_dir = scope::skills_dir(registry.require(agent)?, project)?.join(id);
+    let config_path = skill_dir.join(skill_config::CONFIG_FILE);
+    let skill_md_path = skill_dir.join("SKILL.md");
+
+    let mut current_config = if config_path.exists() {
+        fs::read_to_string(&config_path).map_err(|e| SkillsError::io(&config_path, e))?
+    } else {
+        "{}".to_string()
+    };
+
+    let schema = skill_config::load_schema(&skill_dir)?;
+    if let Some(schema) = &schema {
+        // Show the defaults the config will be saved with; a config that
+        // doesn't parse is left for the user to fix
+        if let Ok(mut value) = serde_json::from_str(&current_config) {
+            if skill_config::apply_defaults(&schema.value, &mut value) {
+                current_config = serde_json::to_string_pretty(&value)
+                    .map_err(|e| SkillsError::Other(e.to_string()))?;
+            }
+        }
+    }
+
+    let documentation = if skill_md_path.exists() {
+        fs::read_to_string(skill_md_path).ok()
+    } else {
+        None
+    };
+
+    Ok(SkillConfigResponse {
+        current_config,
+        documentation,
+        schema: schema.map(|schema| schema.value),
+    })
+}
+
+/// Validates `config` against the skill's schema, if it has one, and saves it
+/// with the schema's defaults filled in and its secrets moved to the vault.
+pub fn save_skill_config(
+    id: &str,
+    agent: &str,
+    config: &str,
+    project: Option<&str>,
+) -> Result<ConfigVersion> {
+    let config_path = skill_config_path(id, agent, project)?;
+    let skill_dir = config_path.parent().unwrap_or(Path::new("."));
+    let schema = skill_config::load_schema(skill_dir)?;
+    let mut value = skill_config::validate(config, schema.as_ref(), &config_path)?;
+    if let Some(schema) = &schema {
+        skill_config::seal_secrets(&schema.value, &mut value, &config_path)?;
+    }
+    // Keep the user's formatting unless defaults or references were added
+    let content = match serde_json::from_str::<serde_json::Value>(config) {
+        Ok(original) if original == value => config.to_string(),
+        _ => serde_json::to_string_pretty(&value).map_err(|e| SkillsError::Other(e.to_string()))?,
+    };
+    config_history::write(&config_path, &content)
+}
+
+/// `skill.config.json` of an installed skill. Links are resolved, so every
+/// agent sharing one copy shares its config history too.
+fn skill_config_path(id: &str, agent: &str, project: Option<&str>) -> Result<PathBuf> {
+    let registry = AgentRegistry::load()?;
+    let skill_dir = scope::skills_dir(registry.require(agent)?, project)?.join(id);
+    if !skill_dir.exists() {
+        return Err(SkillsError::skill_not_found(id, Some(&skill_dir)));
+    }
+    let skill_dir = fs::canonicalize(&skill_dir).map_err(|e| SkillsError::io(&skill_dir, e))?;
+    Ok(skill_dir.join(skill_config::CONFIG_FILE))
+}
+
+/// Saved versions of a skill's config, newest first.
+pub fn config_history(id: &str, agent: &str, project: Option<&str>) -> Result<Vec<ConfigVersion>> {
+    config_history::list(&skill_config_path(id, agent, project)?)
+}
+
+/// Saves an earlier version of a skill's config again, as a new version.
+pub fn restore_config_version(
+    id: &str,
+    agent: &str,
+    version: u64,
+    project: Option<&str>,
+) -> Result<ConfigVersion> {
+    let old = config_history::version(&skill_config_path(id, agent, project)?, version)?;
+    save_skill_config(id, agent, &old.content, project)
+}
+
+/// What changed in a skill's config from version `from` to version `to`.
+pub fn diff_config_versions(
+    id: &str,
+    agent: &str,
+    from: u64,
+    to: u64,
+    project: Option<&str>,
+) -> Result<Vec<ConfigChange>> {
+    config_history::diff(&skill_config_path(id, agent, project)?, from, to)
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SkillUpdateInfo {
+    pub id: String,
+    pub remote_hash: String,
+}
+
+/// Fetches every git-backed skill and reports those behind their remote.
+pub fn check_updates(skills: Vec<Skill>, op: &Operation) -> Result<Vec<SkillUpdateInfo>> {
+    let registry = AgentRegistry::load()?;
+    let mut update_results = Vec::new();
+
+    for skill in skills {
+        op.check()?;
+        let agent = match registry.get(&skill.agent) {
+            Some(agent) => agent,
+            None => continue,
+        };
+
+        let skill_dir = match scope::skills_dir(agent, skill.scope.project_path()) {
+            Ok(dir) => dir.join(&skill.id),
+            Err(_) => continue,
+        };
+        if !skill_dir.exists() {
+            continue;
+        }
+
+        // Only check for non-symlinked git repos for now
+        if !skill.is_symlink && skill_dir.join(".git").exists() {
+            if let Err(e) = git::fetch(&skill_dir, op) {
+                // A timeout or cancel ends the whole check
+                op.check()?;
+                op.log(format!("git fetch failed for {}: {}", skill.id, e));
+                continue;
+            }
+
+            // Compare local HEAD with the branch it tracks
+            let local_hash = git::head(&skill_dir).ok().map(|head| head.commit);
+            let remote_hash = git::upstream_commit(&skill_dir).ok().flatten();
+            if let (Some(local_hash), Some(remote_hash)) = (local_hash, remote_hash) {
+                if local_hash != remote_hash {
+                    update_results.push(SkillUpdateInfo {
+                        id: skill.id,
+                        remote_hash: remote_hash.chars().take(7).collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(update_results)
+}
+/// Fast-forwards a skill checkout to its upstream and returns a summary.
+pub fn update_skill(
+    id: &str,
+    agent: &str,
+    project: Option<&str>,
+    op: &Operation,
+) -> Result<String> {
+    let registry = AgentRegistry::load()?;
+    let skill_dir = scope::skills_dir(registry.require(agent)?, project)?.join(id);
+    if !skill_dir.exists() {
+        return Err(SkillsError::skill_not_found(id, Some(&skill_dir)));
+    }
+
+    op.set_phase(Phase::Updating, 10);
+    git::fast_forward(&skill_dir, op)
+}
+
+pub fn skill_provenance(
+    id: &str,
+    agent: &str,
+    project: Option<&str>,
+) -> Result<Option<SourceRecord>> {
+    let registry = AgentRegistry::load()?;
+    let path = scope::skills_dir(registry.require(agent)?, project)?.join(id);
+    let skill_scope = match project {
+        Some(project) => SkillScope::project(&scope::project_root(project)?),
+        None => SkillScope::for_home_agent(agent),
+    };
+    Ok(SourceIndex::load()?
+        .lookup(&skill_scope, agent, &path)
+        .cloned())
+}
+
+/// One skill in an export, the format the Settings page copies to the clipboard.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExportedSkill {
+    pub id: String,
+    #[serde(default)]
+    pub agents: Vec<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Older exports put the skill name here instead of in `id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+    /// `skill.config.json` of the first copy that has one, secrets redacted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillsExport {
+    #[serde(default)]
+    pub exported_at: Option<String>,
+    pub skills: Vec<ExportedSkill>,
+}
+
+/// Installed skills grouped by id, with the agents that have them and their
+/// config. Secret config values are redacted.
+pub fn export() -> Result<SkillsExport> {
+    let mut skills: Vec<ExportedSkill> = Vec::new();
+    for skill in local_skills()? {
+        let config = || {
+            skill_config::redacted_config(Path::new(&skill.path)).unwrap_or_else(|e| {
+                eprintln!("Failed to export config of {}: {}", skill.id, e);
+                None
+            })
+        };
+        match skills.iter_mut().find(|s| s.id == skill.id) {
+            Some(existing) => {
+                if !existing.agents.contains(&skill.agent) {
+                    existing.agents.push(skill.agent);
+                }
+                if existing.config.is_none() {
+                    existing.config = config();
+                }
+                if existing.source.is_none() {
+                    existing.source = skill.source;
+                }
+            }
+            None => skills.push(ExportedSkill {
+                config: config(),
+                id: skill.id,
+                agents: vec![skill.agent],
+                source: skill.source,
+                skill: None,
+            }),
+        }
+    }
+    Ok(SkillsExport {
+        exported_at: Some(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        skills,
+    })
+}
+
+impl ExportedSkill {
+    /// Resolves what to install the same way the Settings import does: `source`
+    /// wins, otherwise `id` is the source with an optional `--skill <name>`.
+    pub fn to_import_entry(&self, default_agents: &[String], global: bool) -> ImportEntry {
+        let raw_agents = if self.agents.is_empty() {
+            default_agents
+        } else {
+            &self.agents
+        };
+        let agents = raw_agents
+            .iter()
+            .filter(|a| a.as_str() != GLOBAL_AGENT)
+            .cloned()
+            .collect();
+        let global = global || raw_agents.iter().any(|a| a == GLOBAL_AGENT);
+
+        let (source, skill) = match &self.source {
+            Some(source) => (source.clone(), Some(self.id.clone())),
+            None => {
+                let mut parts = self.id.split_whitespace();
+                let mut source = Vec::new();
+                let mut skill = self.skill.clone();
+                while let Some(part) = parts.next() {
+                    if part == "--skill" {
+                        skill = parts.next().map(str::to_string);
+                    } else {
+                        source.push(part);
+                    }
+                }
+                (source.join(" "), skill)
+            }
+        };
+
+        ImportEntry {
+            source,
+            skill,
+            agents,
+            global,
+        }
+    }
+}
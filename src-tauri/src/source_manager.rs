@@ -1,52 +1,515 @@
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+//! Provenance store: where each installed skill came from.
+//!
+//! Entries are keyed by scope, agent and skill path, so two skills with the
+//! same directory name don't overwrite each other. Each entry also keeps a
+//! content hash to recognise skills that were moved or renamed.
+//!
+//! Kept in `skill_sources.json` with a schema version. Older layouts are
+//! migrated on read, writes go through a temp file and a rename, and every
+//! read-modify-write holds a lock on `skill_sources.json.lock` so concurrent
+//! installs (or two app instances) can't drop each other's entries.
+//!
+//! The store lives in the app's data directory. Older builds kept it in
+//! `~/.gemini/antigravity`; it is moved from there on first use and a
+//! `migration-report.json` is left next to it.
 
-// ... existing imports ...
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
 
-// Helper function to get the skill sources file path
-fn get_skill_sources_path() -> Option<PathBuf> {
-    dirs::home_dir().map(|home| home.join(".gemini").join("antigravity").join("skill_sources.json"))
+use crate::digest;
+use crate::error::{Result, SkillsError};
+use crate::installer::{InstallMode, InstallReport};
+use crate::paths;
+use crate::scope::SkillScope;
+use crate::util::{self, now_secs};
+
+const SOURCES_FILE: &str = "skill_sources.json";
+const LOCK_FILE: &str = "skill_sources.json.lock";
+const MIGRATION_REPORT_FILE: &str = "migration-report.json";
+
+/// Version written by this build. Version 0 is the original flat
+/// `{ "<skill>": "<source>" }` map, which has no version field; version 1
+/// keyed records by skill name.
+const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SourceRecord {
+    /// What was passed to the installer: URL, shorthand, path or tarball
+    pub source: String,
+    #[serde(default)]
+    pub reference: Option<String>,
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Seconds since the Unix epoch. Unknown for migrated entries.
+    #[serde(default)]
+    pub installed_at: Option<u64>,
+    #[serde(default)]
+    pub install_mode: Option<InstallMode>,
+    #[serde(default)]
+    pub agents: Vec<String>,
 }
 
-// Ensure the directory exists
-fn ensure_sources_dir_exists() {
-    if let Some(path) = get_skill_sources_path() {
-        if let Some(parent) = path.parent() {
-            let _ = fs::create_dir_all(parent);
+/// Provenance of one installed copy of a skill.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProvenanceEntry {
+    /// Directory name of the skill
+    pub name: String,
+    /// Scope, agent and path are unknown for entries migrated from version 1
+    #[serde(default)]
+    pub scope: Option<SkillScope>,
+    #[serde(default)]
+    pub agent: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Digest of each file at install time, by path relative to the skill
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub record: SourceRecord,
+}
+
+impl ProvenanceEntry {
+    fn is_keyed(&self, scope: &SkillScope, agent: &str, path: &str) -> bool {
+        self.scope.as_ref() == Some(scope)
+            && self.agent.as_deref() == Some(agent)
+            && self.path.as_deref() == Some(path)
+    }
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct SourcesFile {
+    version: u32,
+    #[serde(default)]
+    entries: Vec<ProvenanceEntry>,
+}
+
+fn sources_dir() -> Result<PathBuf> {
+    let dir = paths::data_dir()?;
+    static MIGRATE: Once = Once::new();
+    MIGRATE.call_once(|| {
+        if let Some(report) = migrate_legacy_location(&dir) {
+            if let Err(e) = write_migration_report(&dir, &report) {
+                eprintln!("Failed to write migration report: {}", e);
+            }
+        }
+    });
+    Ok(dir)
+}
+
+// Where versions before the data directory kept the store
+fn legacy_sources_dir() -> Result<PathBuf> {
+    paths::home_dir().map(|home| home.join(".gemini").join("antigravity"))
+}
+
+/// What happened when the store was moved out of the legacy directory.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MigrationReport {
+    pub migrated_at: u64,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub entries: usize,
+    /// Whether the legacy file (and empty parent directories) were deleted
+    pub legacy_removed: bool,
+    pub error: Option<String>,
+}
+
+/// Moves the legacy store into `dir` unless `dir` already has one.
+/// Returns `None` when there was nothing to migrate.
+fn migrate_legacy_location(dir: &Path) -> Option<MigrationReport> {
+    let legacy_dir = legacy_sources_dir().ok()?;
+    let legacy = legacy_dir.join(SOURCES_FILE);
+    let target = dir.join(SOURCES_FILE);
+    if legacy_dir == dir || !legacy.exists() {
+        return None;
+    }
+    // Checked under the lock, so two processes starting at once don't both
+    // copy the legacy store over entries the first one already wrote
+    let lock = lock(dir, true);
+    if target.exists() {
+        return None;
+    }
+
+    let mut report = MigrationReport {
+        migrated_at: now_secs(),
+        from: legacy.clone(),
+        to: target.clone(),
+        entries: 0,
+        legacy_removed: false,
+        error: None,
+    };
+
+    let moved = (|| {
+        let lock = lock?;
+        // Parsing runs the schema migrations, so a corrupt file stays where it is
+        let store = read_store(&legacy)?;
+        write_store(&target, &store)?;
+        Ok::<_, SkillsError>((lock, store.entries.len()))
+    })();
+
+    match moved {
+        Ok((_lock, entries)) => {
+            report.entries = entries;
+            report.legacy_removed = fs::remove_file(&legacy).is_ok();
+            let _ = fs::remove_file(legacy_dir.join(LOCK_FILE));
+            // Only removes directories that are now empty; Antigravity may still use them
+            let _ = fs::remove_dir(&legacy_dir);
+            if let Some(parent) = legacy_dir.parent() {
+                let _ = fs::remove_dir(parent);
+            }
         }
+        Err(e) => report.error = Some(e.to_string()),
+    }
+    Some(report)
+}
+
+fn write_migration_report(dir: &Path, report: &MigrationReport) -> Result<()> {
+    let path = dir.join(MIGRATION_REPORT_FILE);
+    let content =
+        serde_json::to_string_pretty(report).map_err(|e| SkillsError::Other(e.to_string()))?;
+    fs::write(&path, content).map_err(|e| SkillsError::io(&path, e))
+}
+
+/// The report left by the move out of the legacy directory, if there was one.
+pub fn migration_report() -> Result<Option<MigrationReport>> {
+    let path = sources_dir()?.join(MIGRATION_REPORT_FILE);
+    if !path.exists() {
+        return Ok(None);
     }
+    let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| SkillsError::invalid_config(&path, e))
 }
 
-pub fn save_skill_source(skill_id: &str, source: &str) {
-    ensure_sources_dir_exists();
-    if let Some(path) = get_skill_sources_path() {
-        let mut sources: HashMap<String, String> = if path.exists() {
-            fs::read_to_string(&path)
-                .ok()
-                .and_then(|content| serde_json::from_str(&content).ok())
-                .unwrap_or_default()
-        } else {
-            HashMap::new()
+/// Upgrades a parsed file one version at a time until it's current.
+fn migrate(mut value: serde_json::Value, path: &Path) -> Result<SourcesFile> {
+    loop {
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        value = match version {
+            0 => migrate_v0(value, path)?,
+            1 => migrate_v1(value, path)?,
+            SCHEMA_VERSION => {
+                return serde_json::from_value(value)
+                    .map_err(|e| SkillsError::invalid_config(path, e))
+            }
+            newer => {
+                return Err(SkillsError::invalid_config(
+                    path,
+                    format!("schema version {} is newer than this app supports", newer),
+                ))
+            }
         };
+    }
+}
 
-        sources.insert(skill_id.to_string(), source.to_string());
+// v0 -> v1: flat map of skill id to source string
+fn migrate_v0(value: serde_json::Value, path: &Path) -> Result<serde_json::Value> {
+    let flat: BTreeMap<String, String> =
+        serde_json::from_value(value).map_err(|e| SkillsError::invalid_config(path, e))?;
+    let skills: BTreeMap<String, SourceRecord> = flat
+        .into_iter()
+        .map(|(id, source)| {
+            let record = SourceRecord {
+                source,
+                reference: None,
+                commit: None,
+                installed_at: None,
+                install_mode: None,
+                agents: Vec::new(),
+            };
+            (id, record)
+        })
+        .collect();
+    Ok(serde_json::json!({ "version": 1, "skills": skills }))
+}
 
-        if let Ok(content) = serde_json::to_string_pretty(&sources) {
-            let _ = fs::write(path, content);
-        }
+// v1 -> v2: records keyed by name become entries without a location
+fn migrate_v1(value: serde_json::Value, path: &Path) -> Result<serde_json::Value> {
+    #[derive(serde::Deserialize)]
+    struct V1 {
+        #[serde(default)]
+        skills: BTreeMap<String, SourceRecord>,
     }
+
+    let v1: V1 = serde_json::from_value(value).map_err(|e| SkillsError::invalid_config(path, e))?;
+    let entries: Vec<ProvenanceEntry> = v1
+        .skills
+        .into_iter()
+        .map(|(name, record)| ProvenanceEntry {
+            name,
+            scope: None,
+            agent: None,
+            path: None,
+            content_hash: None,
+            files: BTreeMap::new(),
+            record,
+        })
+        .collect();
+    Ok(serde_json::json!({ "version": 2, "entries": entries }))
 }
 
-pub fn get_skill_source(skill_id: &str) -> Option<String> {
-    if let Some(path) = get_skill_sources_path() {
-        if path.exists() {
-            let sources: HashMap<String, String> = fs::read_to_string(path)
-                .ok()
-                .and_then(|content| serde_json::from_str(&content).ok())
-                .unwrap_or_default();
-            return sources.get(skill_id).cloned();
+fn read_store(path: &Path) -> Result<SourcesFile> {
+    if !path.exists() {
+        return Ok(SourcesFile {
+            version: SCHEMA_VERSION,
+            entries: Vec::new(),
+        });
+    }
+    let content = fs::read_to_string(path).map_err(|e| SkillsError::io(path, e))?;
+    if content.trim().is_empty() {
+        return Ok(SourcesFile {
+            version: SCHEMA_VERSION,
+            entries: Vec::new(),
+        });
+    }
+    let value = serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(path, e))?;
+    migrate(value, path)
+}
+
+fn write_store(path: &Path, store: &SourcesFile) -> Result<()> {
+    let content =
+        serde_json::to_string_pretty(store).map_err(|e| SkillsError::Other(e.to_string()))?;
+    util::write_atomic(path, content.as_bytes())
+}
+
+/// Cross-process lock, released when dropped.
+fn lock(dir: &Path, exclusive: bool) -> Result<File> {
+    util::lock(&dir.join(LOCK_FILE), exclusive)
+}
+
+/// Runs `f` on the store under an exclusive lock and saves the result.
+fn update<R>(f: impl FnOnce(&mut SourcesFile) -> R) -> Result<R> {
+    let dir = sources_dir()?;
+    let _lock = lock(&dir, true)?;
+    let path = dir.join(SOURCES_FILE);
+
+    let mut store = read_store(&path)?;
+    let result = f(&mut store);
+    store.version = SCHEMA_VERSION;
+    write_store(&path, &store)?;
+    Ok(result)
+}
+
+fn load() -> Result<SourcesFile> {
+    let dir = sources_dir()?;
+    let _lock = lock(&dir, false)?;
+    read_store(&dir.join(SOURCES_FILE))
+}
+
+/// Records provenance for every installed copy in `report`. `project` is the
+/// project the install targeted, if any. Returns the skill names saved.
+pub fn record_install(report: &InstallReport, project: Option<&Path>) -> Result<Vec<String>> {
+    let installed_at = now_secs();
+    let mut entries = Vec::new();
+
+    for skill in &report.installed {
+        let Some(path) = &skill.path else { continue };
+        let scope = match project {
+            Some(root) => SkillScope::project(root),
+            None => SkillScope::for_home_agent(&skill.agent),
+        };
+        let agents = report
+            .installed
+            .iter()
+            .filter(|s| s.name == skill.name)
+            .map(|s| s.agent.clone())
+            .collect();
+
+        entries.push(ProvenanceEntry {
+            name: skill.name.clone(),
+            scope: Some(scope),
+            agent: Some(skill.agent.clone()),
+            path: Some(path.clone()),
+            content_hash: digest::dir_digest(Path::new(path)).ok(),
+            files: digest::file_digests(Path::new(path)).unwrap_or_default(),
+            record: SourceRecord {
+                source: report.source.clone(),
+                reference: report.reference.clone(),
+                commit: report.commit.clone(),
+                installed_at: Some(installed_at),
+                install_mode: Some(skill.mode),
+                agents,
+            },
+        });
+    }
+
+    let mut names: Vec<String> = entries.iter().map(|e| e.name.clone()).collect();
+    names.sort();
+    names.dedup();
+
+    update(|store| {
+        for entry in entries {
+            let (scope, agent, path) = (
+                entry.scope.clone().unwrap_or_default(),
+                entry.agent.clone().unwrap_or_default(),
+                entry.path.clone().unwrap_or_default(),
+            );
+            store.entries.retain(|e| !e.is_keyed(&scope, &agent, &path));
+            store.entries.push(entry);
         }
+    })?;
+    Ok(names)
+}
+
+/// Removes and returns the entry for the skill at `path`, e.g. when it is
+/// moved to the trash.
+pub fn take_entry(scope: &SkillScope, agent: &str, path: &Path) -> Result<Option<ProvenanceEntry>> {
+    let key = path.to_string_lossy();
+    update(|store| {
+        let index = store
+            .entries
+            .iter()
+            .position(|e| e.is_keyed(scope, agent, &key))?;
+        Some(store.entries.remove(index))
+    })
+}
+
+/// Puts back an entry returned by [`take_entry`].
+pub fn restore_entry(entry: ProvenanceEntry) -> Result<()> {
+    update(|store| {
+        if let (Some(scope), Some(agent), Some(path)) = (&entry.scope, &entry.agent, &entry.path) {
+            store.entries.retain(|e| !e.is_keyed(scope, agent, path));
+        }
+        store.entries.push(entry);
+    })
+}
+
+/// A snapshot of the store for looking up many skills at once.
+pub struct SourceIndex {
+    entries: Vec<ProvenanceEntry>,
+}
+
+impl SourceIndex {
+    pub fn load() -> Result<Self> {
+        Ok(SourceIndex {
+            entries: load()?.entries,
+        })
+    }
+
+    /// The entry recorded when the skill at `path` was installed there.
+    pub fn entry(&self, scope: &SkillScope, agent: &str, path: &Path) -> Option<&ProvenanceEntry> {
+        let key = path.to_string_lossy();
+        self.entries.iter().find(|e| e.is_keyed(scope, agent, &key))
+    }
+
+    /// Finds where the skill at `path` came from: by its exact location first,
+    /// then by content hash (it was moved or renamed), then by a legacy entry
+    /// with the same directory name.
+    pub fn lookup(&self, scope: &SkillScope, agent: &str, path: &Path) -> Option<&SourceRecord> {
+        self.lookup_with(scope, agent, path, || digest::dir_digest(path).ok())
+    }
+
+    /// [`SourceIndex::lookup`] with the skill's content hash supplied by the
+    /// caller, which is only asked for it when the location doesn't match.
+    pub fn lookup_with(
+        &self,
+        scope: &SkillScope,
+        agent: &str,
+        path: &Path,
+        content_hash: impl FnOnce() -> Option<String>,
+    ) -> Option<&SourceRecord> {
+        if let Some(entry) = self.entry(scope, agent, path) {
+            return Some(&entry.record);
+        }
+
+        if self.entries.iter().any(|e| e.content_hash.is_some()) {
+            if let Some(hash) = content_hash() {
+                let newest = self
+                    .entries
+                    .iter()
+                    .filter(|e| e.content_hash.as_deref() == Some(hash.as_str()))
+                    .max_by_key(|e| e.record.installed_at);
+                if let Some(entry) = newest {
+                    return Some(&entry.record);
+                }
+            }
+        }
+
+        let name = path.file_name()?.to_string_lossy();
+        self.entries
+            .iter()
+            .find(|e| e.agent.is_none() && e.name == name)
+            .map(|e| &e.record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn migrated(value: serde_json::Value) -> Result<SourcesFile> {
+        migrate(value, Path::new("skill_sources.json"))
+    }
+
+    #[test]
+    fn migrates_flat_map_from_v0() {
+        let store = migrated(json!({
+            "pdf": "anthropics/skills",
+            "docx": "https://github.com/acme/docx.git",
+        }))
+        .unwrap();
+        assert_eq!(store.version, SCHEMA_VERSION);
+        let names: Vec<&str> = store.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["docx", "pdf"]);
+        let pdf = &store.entries[1];
+        assert_eq!(pdf.record.source, "anthropics/skills");
+        assert!(pdf.scope.is_none() && pdf.agent.is_none() && pdf.path.is_none());
+        assert!(pdf.record.installed_at.is_none());
+    }
+
+    #[test]
+    fn migrates_records_from_v1() {
+        let store = migrated(json!({
+            "version": 1,
+            "skills": {
+                "pdf": {
+                    "source": "anthropics/skills",
+                    "reference": "v1.2.0",
+                    "commit": "abc123",
+                    "installed_at": 1700000000,
+                    "install_mode": "copy",
+                    "agents": ["claude-code", "cursor"],
+                }
+            }
+        }))
+        .unwrap();
+        let [entry] = store.entries.as_slice() else {
+            panic!("expected one entry, got {:?}", store.entries);
+        };
+        assert_eq!(entry.name, "pdf");
+        assert_eq!(entry.record.reference.as_deref(), Some("v1.2.0"));
+        assert_eq!(entry.record.commit.as_deref(), Some("abc123"));
+        assert_eq!(entry.record.installed_at, Some(1700000000));
+        assert_eq!(entry.record.install_mode, Some(InstallMode::Copy));
+        assert_eq!(entry.record.agents, ["claude-code", "cursor"]);
+        assert!(entry.content_hash.is_none());
+    }
+
+    #[test]
+    fn current_version_is_read_as_is() {
+        let store = migrated(json!({
+            "version": SCHEMA_VERSION,
+            "entries": [{
+                "name": "pdf",
+                "agent": "cursor",
+                "path": "/home/me/.cursor/skills/pdf",
+                "content_hash": "sha256:00",
+                "source": "anthropics/skills",
+            }]
+        }))
+        .unwrap();
+        assert_eq!(store.entries[0].agent.as_deref(), Some("cursor"));
+        assert_eq!(store.entries[0].content_hash.as_deref(), Some("sha256:00"));
+    }
+
+    #[test]
+    fn rejects_newer_and_malformed_stores() {
+        assert!(migrated(json!({ "version": SCHEMA_VERSION + 1 })).is_err());
+        assert!(migrated(json!({ "pdf": { "source": "nested" } })).is_err());
     }
-    None
 }
@@ -0,0 +1,658 @@
+//! Team manifest (`skills.toml`): the skill sources a team wants installed,
+//! which agents get them and how.
+//!
+//! ```toml
+//! version = 1
+//!
+//! [defaults]
+//! agents = ["claude-code", "cursor"]
+//! mode = "symlink"
+//!
+//! [[sources]]
+//! source = "anthropics/skills"
+//! ref = "v1.2.0"
+//! skills = ["pdf", "docx"]
+//! ```
+//!
+//! [`plan`] compares a manifest with what's installed and lists the installs,
+//! upgrades and removals that would make them match; [`apply`] carries them out.
+//! Only agents named in the manifest are touched, and only skills this app
+//! installed there (those with provenance) are removed; skills written by
+//! hand are left alone.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::agents::{AgentRegistry, GLOBAL_AGENT};
+use crate::error::{Result, SkillsError};
+use crate::installer::{
+    same_dir, Backend, ImportResult, InstallMode, InstallRequest, RemoveRequest,
+};
+use crate::operations::Operation;
+use crate::paths;
+use crate::scope;
+use crate::skills::{self, Skill};
+use crate::source_manager::{SourceIndex, SourceRecord};
+
+pub const MANIFEST_FILE: &str = "skills.toml";
+
+/// Version written by this build.
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TeamManifest {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "ManifestDefaults::is_empty")]
+    pub defaults: ManifestDefaults,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<ManifestSource>,
+}
+
+fn default_version() -> u32 {
+    MANIFEST_VERSION
+}
+
+/// Settings for sources that don't give their own.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ManifestDefaults {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<InstallMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<bool>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ManifestSource {
+    /// Anything the installer accepts: git URL, `owner/repo` shorthand, path
+    pub source: String,
+    /// Branch, tag or commit to install
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Skills to take from the source; all of them when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skills: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<InstallMode>,
+    /// Also install into the shared global store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<bool>,
+}
+
+impl ManifestDefaults {
+    fn is_empty(&self) -> bool {
+        self.agents.is_empty() && self.mode.is_none() && self.global.is_none()
+    }
+}
+
+impl TeamManifest {
+    pub fn parse(content: &str) -> Result<Self> {
+        let manifest: TeamManifest = toml::from_str(content)
+            .map_err(|e| SkillsError::InvalidInput(format!("Invalid {}: {}", MANIFEST_FILE, e)))?;
+        if manifest.version > MANIFEST_VERSION {
+            return Err(SkillsError::InvalidInput(format!(
+                "{} version {} is newer than this app supports ({})",
+                MANIFEST_FILE, manifest.version, MANIFEST_VERSION
+            )));
+        }
+        for entry in &manifest.sources {
+            if manifest.agents(entry).is_empty() && !manifest.global(entry) {
+                return Err(SkillsError::InvalidInput(format!(
+                    "{} lists no agents for {}",
+                    MANIFEST_FILE, entry.source
+                )));
+            }
+        }
+        Ok(manifest)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| SkillsError::Other(e.to_string()))
+    }
+
+    fn agents<'a>(&'a self, entry: &'a ManifestSource) -> &'a [String] {
+        if entry.agents.is_empty() {
+            &self.defaults.agents
+        } else {
+            &entry.agents
+        }
+    }
+
+    fn mode(&self, entry: &ManifestSource) -> InstallMode {
+        entry
+            .mode
+            .or(self.defaults.mode)
+            .unwrap_or(InstallMode::Symlink)
+    }
+
+    fn global(&self, entry: &ManifestSource) -> bool {
+        entry.global.or(self.defaults.global).unwrap_or(false)
+    }
+}
+
+/// One install to run. `skill` is `None` to install everything in the source.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SyncStep {
+    pub source: String,
+    pub reference: Option<String>,
+    pub skill: Option<String>,
+    pub agents: Vec<String>,
+    pub global: bool,
+    pub mode: InstallMode,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SyncRemoval {
+    pub skill: String,
+    pub agent: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct SyncPlan {
+    pub install: Vec<SyncStep>,
+    pub upgrade: Vec<SyncStep>,
+    /// Installed copies with provenance the manifest no longer wants
+    pub remove: Vec<SyncRemoval>,
+    /// Installed copies that already match the manifest
+    pub unchanged: usize,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.upgrade.is_empty() && self.remove.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RemovalResult {
+    pub skill: String,
+    pub agent: String,
+    pub error: Option<SkillsError>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct SyncReport {
+    pub installed: Vec<ImportResult>,
+    pub removed: Vec<RemovalResult>,
+}
+
+impl SyncReport {
+    pub fn failures(&self) -> usize {
+        self.installed.iter().filter(|r| r.error.is_some()).count()
+            + self.removed.iter().filter(|r| r.error.is_some()).count()
+    }
+}
+
+/// An installed copy and where it came from.
+struct Installed {
+    skill: Skill,
+    record: Option<SourceRecord>,
+    /// Whether provenance was recorded for this very copy
+    recorded: bool,
+}
+
+fn installed_skills(project: Option<&Path>) -> Result<HashMap<PathBuf, Installed>> {
+    let index = SourceIndex::load().ok();
+    Ok(skills::skills_in(project)?
+        .into_iter()
+        .map(|skill| {
+            let path = PathBuf::from(&skill.path);
+            let record = index
+                .as_ref()
+                .and_then(|index| index.lookup(&skill.scope, &skill.agent, &path))
+                .cloned();
+            let recorded = index
+                .as_ref()
+                .is_some_and(|index| index.entry(&skill.scope, &skill.agent, &path).is_some());
+            (
+                path,
+                Installed {
+                    skill,
+                    record,
+                    recorded,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Why an installed copy doesn't match `entry`, if it doesn't.
+fn mismatch(
+    installed: &Installed,
+    entry: &ManifestSource,
+    mode: InstallMode,
+    agent: &str,
+) -> Option<String> {
+    let Some(record) = &installed.record else {
+        return Some("installed from an unknown source".to_string());
+    };
+    if record.source != entry.source {
+        return Some(format!("installed from {}", record.source));
+    }
+    if let Some(pin) = &entry.reference {
+        let at_pin = record.reference.as_ref() == Some(pin)
+            || record
+                .commit
+                .as_ref()
+                .is_some_and(|c| c.starts_with(pin.as_str()));
+        if !at_pin {
+            let current = record
+                .reference
+                .clone()
+                .or_else(|| record.commit.clone())
+                .unwrap_or_else(|| "unknown ref".to_string());
+            return Some(format!("at {}, manifest pins {}", current, pin));
+        }
+    }
+    // The global store always holds real copies
+    if agent != GLOBAL_AGENT && installed.skill.is_symlink != (mode == InstallMode::Symlink) {
+        return Some(match mode {
+            InstallMode::Symlink => "installed as a copy, manifest wants a symlink".to_string(),
+            InstallMode::Copy => "installed as a symlink, manifest wants a copy".to_string(),
+        });
+    }
+    None
+}
+
+/// Adds a step for `agents`, merging with an earlier step for the same skill.
+fn push_step(
+    steps: &mut Vec<SyncStep>,
+    entry: &ManifestSource,
+    skill: Option<&str>,
+    agents: Vec<String>,
+    mode: InstallMode,
+    reason: String,
+) {
+    if agents.is_empty() {
+        return;
+    }
+    let global = agents.iter().any(|a| a == GLOBAL_AGENT);
+    let agents: Vec<String> = agents.into_iter().filter(|a| a != GLOBAL_AGENT).collect();
+    steps.push(SyncStep {
+        source: entry.source.clone(),
+        reference: entry.reference.clone(),
+        skill: skill.map(str::to_string),
+        agents,
+        global,
+        mode,
+        reason,
+    });
+}
+
+/// What it takes to make the home directory (or `project`) match `manifest`.
+pub fn plan(manifest: &TeamManifest, project: Option<&Path>) -> Result<SyncPlan> {
+    let registry = AgentRegistry::load()?;
+    let home = paths::home_dir()?;
+    let installed = installed_skills(project)?;
+    let agent_dir = |id: &str| -> Result<PathBuf> {
+        let agent = registry.require(id)?;
+        Ok(match project {
+            Some(root) => agent.project_skills_dir(root),
+            None => agent.skills_dir(&home),
+        })
+    };
+    let store_dir = agent_dir(GLOBAL_AGENT)?;
+
+    let mut plan = SyncPlan::default();
+    // Directories the manifest manages, and the skill paths it wants in them
+    let mut managed: HashMap<PathBuf, String> = HashMap::new();
+    let mut wanted: HashSet<PathBuf> = HashSet::new();
+
+    for entry in &manifest.sources {
+        let mode = manifest.mode(entry);
+        let mut targets: Vec<String> = manifest.agents(entry).to_vec();
+        if manifest.global(entry) && !targets.iter().any(|a| a == GLOBAL_AGENT) {
+            targets.insert(0, GLOBAL_AGENT.to_string());
+        }
+        let mut dirs: Vec<(String, PathBuf)> = Vec::new();
+        for agent in &targets {
+            let dir = agent_dir(agent)?;
+            // Agents keeping their skills in the store get the store's copies
+            let agent = match same_dir(&dir, &store_dir) {
+                true => GLOBAL_AGENT.to_string(),
+                false => agent.clone(),
+            };
+            if dirs.iter().any(|(_, d)| d == &dir) {
+                continue;
+            }
+            managed.entry(dir.clone()).or_insert_with(|| agent.clone());
+            dirs.push((agent, dir));
+        }
+
+        if entry.skills.is_empty() {
+            // Names aren't known until the source is fetched, so go by provenance
+            let mut missing = Vec::new();
+            let mut stale: BTreeMap<String, (Vec<String>, String)> = BTreeMap::new();
+            for (agent, dir) in &dirs {
+                let from_source: Vec<(&PathBuf, &Installed)> = installed
+                    .iter()
+                    .filter(|(path, i)| {
+                        path.parent() == Some(dir.as_path())
+                            && i.record.as_ref().is_some_and(|r| r.source == entry.source)
+                    })
+                    .collect();
+                if from_source.is_empty() {
+                    missing.push(agent.clone());
+                }
+                for (path, i) in from_source {
+                    wanted.insert(path.clone());
+                    if mode == InstallMode::Symlink {
+                        wanted.insert(store_dir.join(&i.skill.id));
+                    }
+                    match mismatch(i, entry, mode, agent) {
+                        Some(reason) => {
+                            let step = stale.entry(i.skill.id.clone()).or_default();
+                            step.0.push(agent.clone());
+                            step.1 = reason;
+                        }
+                        None => plan.unchanged += 1,
+                    }
+                }
+            }
+            push_step(
+                &mut plan.install,
+                entry,
+                None,
+                missing,
+                mode,
+                "not installed".to_string(),
+            );
+            for (skill, (agents, reason)) in stale {
+                push_step(&mut plan.upgrade, entry, Some(&skill), agents, mode, reason);
+            }
+            continue;
+        }
+
+        for skill in &entry.skills {
+            let mut missing = Vec::new();
+            let mut stale = Vec::new();
+            let mut reason = String::new();
+            for (agent, dir) in &dirs {
+                let path = dir.join(skill);
+                match installed.get(&path) {
+                    None => missing.push(agent.clone()),
+                    Some(i) => match mismatch(i, entry, mode, agent) {
+                        Some(why) => {
+                            stale.push(agent.clone());
+                            reason = why;
+                        }
+                        None => plan.unchanged += 1,
+                    },
+                }
+                wanted.insert(path);
+            }
+            if mode == InstallMode::Symlink {
+                wanted.insert(store_dir.join(skill));
+            }
+            push_step(
+                &mut plan.install,
+                entry,
+                Some(skill),
+                missing,
+                mode,
+                "not installed".to_string(),
+            );
+            push_step(&mut plan.upgrade, entry, Some(skill), stale, mode, reason);
+        }
+    }
+
+    let mut removals: Vec<SyncRemoval> = installed
+        .iter()
+        .filter(|(path, i)| i.recorded && !wanted.contains(*path))
+        .filter_map(|(path, i)| {
+            let agent = managed.get(path.parent()?)?;
+            Some(SyncRemoval {
+                skill: i.skill.id.clone(),
+                agent: agent.clone(),
+                path: path.to_string_lossy().to_string(),
+            })
+        })
+        .collect();
+    // Links go before the store copies they point to, which can't be
+    // removed while still linked
+    removals.sort_by_key(|r| {
+        let in_store = Path::new(&r.path).parent() == Some(store_dir.as_path());
+        (in_store, r.path.clone())
+    });
+    plan.remove = removals;
+
+    Ok(plan)
+}
+
+/// Runs [`plan`] and carries it out with the native installer. Failed steps
+/// are reported and don't stop the rest. `frozen` installs exactly what
+/// skills.lock records, see [`crate::lockfile`].
+pub fn apply(
+    manifest: &TeamManifest,
+    project: Option<&Path>,
+    frozen: bool,
+    op: &Operation,
+) -> Result<SyncReport> {
+    let plan = plan(manifest, project)?;
+    let steps: Vec<&SyncStep> = plan.install.iter().chain(&plan.upgrade).collect();
+    let total = (steps.len() + plan.remove.len()).max(1);
+    let mut report = SyncReport::default();
+
+    for (index, step) in steps.iter().enumerate() {
+        let result = match op.interrupted() {
+            Some(reason) => Err(reason.into()),
+            None => {
+                op.log(format!(
+                    "Installing {}{} ({})",
+                    step.source,
+                    step.skill
+                        .as_ref()
+                        .map(|s| format!(" --skill {}", s))
+                        .unwrap_or_default(),
+                    step.reason
+                ));
+                let request = InstallRequest {
+                    source: step.source.clone(),
+                    reference: step.reference.clone(),
+                    skill: step.skill.clone(),
+                    global: step.global,
+                    agents: step.agents.clone(),
+                    mode: step.mode,
+                    project: project.map(Path::to_path_buf),
+                    auto_confirm: true,
+                    frozen,
+                };
+                let scoped = op.scoped(
+                    (100 * index / total) as u8,
+                    (100 * (index + 1) / total) as u8,
+                );
+                skills::install(&request, Backend::Native, &scoped)
+            }
+        };
+        if let Err(ref e) = result {
+            op.log(format!("Failed to install {}: {}", step.source, e));
+        }
+        report.installed.push(ImportResult {
+            source: step.source.clone(),
+            skill: step.skill.clone(),
+            error: result.as_ref().err().cloned(),
+            report: result.ok(),
+        });
+    }
+
+    for removal in &plan.remove {
+        let result = match op.interrupted() {
+            Some(reason) => Err(reason.into()),
+            None => {
+                op.log(format!("Removing {} from {}", removal.skill, removal.agent));
+                let request = RemoveRequest {
+                    skill_ids: vec![removal.skill.clone()],
+                    global: false,
+                    agents: vec![removal.agent.clone()],
+                    remove_all: false,
+                    project: project.map(Path::to_path_buf),
+                    auto_confirm: true,
+                    cascade: false,
+                };
+                skills::remove(&request, Backend::Native, op).map(|_| ())
+            }
+        };
+        report.removed.push(RemovalResult {
+            skill: removal.skill.clone(),
+            agent: removal.agent.clone(),
+            error: result.err(),
+        });
+    }
+
+    Ok(report)
+}
+
+/// A manifest describing what's installed in the home directory (or `project`).
+/// Skills with no recorded source are left out since they can't be reinstalled.
+pub fn export(project: Option<&Path>) -> Result<TeamManifest> {
+    // (source, ref, mode) -> skill -> agents
+    type Groups =
+        BTreeMap<(String, Option<String>, InstallMode), BTreeMap<String, BTreeSet<String>>>;
+    let mut groups: Groups = BTreeMap::new();
+    let mut store_only: Vec<(String, Option<String>, String)> = Vec::new();
+
+    let installed = installed_skills(project)?;
+    let mut sorted: Vec<&Installed> = installed.values().collect();
+    sorted.sort_by(|a, b| a.skill.path.cmp(&b.skill.path));
+
+    for i in &sorted {
+        let Some(record) = &i.record else { continue };
+        if i.skill.agent == GLOBAL_AGENT {
+            continue;
+        }
+        let mode = if i.skill.is_symlink {
+            InstallMode::Symlink
+        } else {
+            InstallMode::Copy
+        };
+        groups
+            .entry((record.source.clone(), record.reference.clone(), mode))
+            .or_default()
+            .entry(i.skill.id.clone())
+            .or_default()
+            .insert(i.skill.agent.clone());
+    }
+
+    // Skills only in the global store, not linked from any agent
+    for i in &sorted {
+        let Some(record) = &i.record else { continue };
+        if i.skill.agent != GLOBAL_AGENT {
+            continue;
+        }
+        let linked = groups
+            .values()
+            .any(|skills| skills.contains_key(&i.skill.id));
+        if !linked {
+            store_only.push((
+                record.source.clone(),
+                record.reference.clone(),
+                i.skill.id.clone(),
+            ));
+        }
+    }
+
+    let mut sources = Vec::new();
+    for ((source, reference, mode), skills) in groups {
+        // One entry per distinct agent set, so each lists its own skills
+        let mut by_agents: BTreeMap<Vec<String>, Vec<String>> = BTreeMap::new();
+        for (skill, agents) in skills {
+            by_agents
+                .entry(agents.into_iter().collect())
+                .or_default()
+                .push(skill);
+        }
+        for (agents, skills) in by_agents {
+            sources.push(ManifestSource {
+                source: source.clone(),
+                reference: reference.clone(),
+                skills,
+                agents,
+                mode: Some(mode),
+                global: None,
+            });
+        }
+    }
+    for (source, reference, skill) in store_only {
+        sources.push(ManifestSource {
+            source,
+            reference,
+            skills: vec![skill],
+            agents: Vec::new(),
+            mode: Some(InstallMode::Copy),
+            global: Some(true),
+        });
+    }
+
+    Ok(TeamManifest {
+        version: MANIFEST_VERSION,
+        defaults: ManifestDefaults::default(),
+        sources,
+    })
+}
+
+/// Parses `manifest` and resolves `project` the way every command needs.
+pub fn load(manifest: &str, project: Option<&str>) -> Result<(TeamManifest, Option<PathBuf>)> {
+    let manifest = TeamManifest::parse(manifest)?;
+    let project = project.map(scope::project_root).transpose()?;
+    Ok((manifest, project))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::test_support::{operation, workspace, write_skill};
+
+    #[test]
+    fn removes_links_before_the_store_copy() {
+        let workspace = workspace();
+        let root = workspace.root.clone();
+        let source = tempfile::tempdir().unwrap();
+        write_skill(&source.path().join("pdf"), "pdf");
+        write_skill(&source.path().join("docx"), "docx");
+        let source = source.path().to_string_lossy().to_string();
+
+        let request = InstallRequest {
+            source: source.clone(),
+            reference: None,
+            skill: None,
+            global: true,
+            agents: vec!["claude-code".to_string()],
+            mode: InstallMode::Symlink,
+            project: Some(root.clone()),
+            auto_confirm: true,
+            frozen: false,
+        };
+        skills::install(&request, Backend::Native, &operation()).unwrap();
+
+        let manifest = TeamManifest::parse(&format!(
+            "[[sources]]\nsource = {:?}\nskills = [\"docx\"]\nagents = [\"claude-code\"]\nglobal = true\nmode = \"symlink\"\n",
+            source
+        ))
+        .unwrap();
+        let link = root.join(".claude/skills/pdf");
+        let store_copy = root.join(".agents/skills/pdf");
+        let planned = plan(&manifest, Some(&root)).unwrap();
+        let removing: Vec<&str> = planned.remove.iter().map(|r| r.path.as_str()).collect();
+        assert_eq!(
+            removing,
+            [
+                link.to_string_lossy().as_ref(),
+                store_copy.to_string_lossy().as_ref()
+            ]
+        );
+
+        let report = apply(&manifest, Some(&root), false, &operation()).unwrap();
+        assert_eq!(report.failures(), 0, "{:?}", report.removed);
+        assert!(fs::symlink_metadata(&link).is_err());
+        assert!(fs::symlink_metadata(&store_copy).is_err());
+        assert!(root.join(".claude/skills/docx").exists());
+    }
+}
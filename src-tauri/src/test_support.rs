@@ -0,0 +1,69 @@
+//! Shared setup for tests that touch the home and data directories.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use crate::agents::AgentRegistry;
+use crate::operations::{NullSink, Operation};
+use crate::workspaces;
+
+/// Points the home, config and data directories at a temp directory kept
+/// for the whole test run, so no test reads or writes the real ones.
+pub fn sandbox() -> &'static Path {
+    static HOME: OnceLock<PathBuf> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = tempfile::tempdir().unwrap().keep();
+        std::env::set_var("HOME", &home);
+        std::env::set_var("XDG_CONFIG_HOME", home.join(".config"));
+        std::env::set_var("XDG_DATA_HOME", home.join(".local/share"));
+        std::env::set_var(crate::paths::DATA_DIR_ENV, home.join("data"));
+        home
+    })
+}
+
+/// The built-in agents, without the user's agents.json.
+pub fn registry() -> AgentRegistry {
+    AgentRegistry::with_overrides(&[])
+}
+
+pub fn operation() -> Operation {
+    Operation::start("test", None, Arc::new(NullSink))
+}
+
+/// Writes a minimal skill called `name` into `dir`.
+pub fn write_skill(dir: &Path, name: &str) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        dir.join("SKILL.md"),
+        format!("---\nname: {name}\ndescription: Test skill {name}\n---\n"),
+    )
+    .unwrap();
+}
+
+/// A temp project registered as a workspace until dropped. The workspace
+/// list is shared, so tests holding one run one at a time.
+pub struct Workspace {
+    pub root: PathBuf,
+    _dir: tempfile::TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+pub fn workspace() -> Workspace {
+    static LOCK: Mutex<()> = Mutex::new(());
+    sandbox();
+    let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = tempfile::tempdir().unwrap();
+    let root = workspaces::add_workspace(&dir.path().to_string_lossy()).unwrap();
+    Workspace {
+        root,
+        _dir: dir,
+        _lock: lock,
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        let _ = workspaces::remove_workspace(&self.root.to_string_lossy());
+    }
+}
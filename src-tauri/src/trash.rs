@@ -0,0 +1,371 @@
+//! App-managed trash for removed skills.
+//!
+//! Removing a skill moves its directory to `<data dir>/trash/<id>/content`
+//! with an `entry.json` recording where it was installed and where it came
+//! from, so it can be put back. For a symlinked skill only the link is
+//! removed; the entry records its target. Entries older than the retention
+//! setting are purged whenever something is trashed or the trash is listed.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::error::{Result, SkillsError};
+use crate::installer::place;
+use crate::paths;
+use crate::scope::SkillScope;
+use crate::settings;
+use crate::source_manager::{self, ProvenanceEntry};
+use crate::util::now_secs;
+
+const TRASH_DIR: &str = "trash";
+const ENTRY_FILE: &str = "entry.json";
+const CONTENT_DIR: &str = "content";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub name: String,
+    pub agent: String,
+    pub scope: SkillScope,
+    /// Where the skill was installed, and is restored to
+    pub origin: String,
+    /// Seconds since the Unix epoch
+    pub deleted_at: u64,
+    /// Set when the skill was a symlink; only the link was removed
+    #[serde(default)]
+    pub link_target: Option<String>,
+    /// The provenance entry, taken out of the store while trashed
+    #[serde(default)]
+    pub provenance: Option<ProvenanceEntry>,
+}
+
+fn trash_dir() -> Result<PathBuf> {
+    Ok(paths::data_dir()?.join(TRASH_DIR))
+}
+
+/// The directory of the entry `id`. Ids come from the frontend, so anything
+/// that isn't a plain directory name is rejected.
+fn entry_dir(id: &str) -> Result<PathBuf> {
+    if id.is_empty() || Path::new(id).file_name() != Some(id.as_ref()) {
+        return Err(SkillsError::InvalidInput(format!(
+            "Invalid trash entry: {}",
+            id
+        )));
+    }
+    Ok(trash_dir()?.join(id))
+}
+
+fn read_entry(dir: &Path) -> Result<TrashEntry> {
+    let path = dir.join(ENTRY_FILE);
+    let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+    serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))
+}
+
+/// Moves the skill at `path` to the trash.
+pub fn move_to_trash(path: &Path, agent: &str, scope: &SkillScope) -> Result<TrashEntry> {
+    if let Err(e) = purge_expired() {
+        eprintln!("Failed to purge expired trash: {}", e);
+    }
+
+    let metadata = fs::symlink_metadata(path).map_err(|e| SkillsError::io(path, e))?;
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let deleted_at = now_secs();
+    let root = trash_dir()?;
+    fs::create_dir_all(&root).map_err(|e| SkillsError::io(&root, e))?;
+
+    // Same name trashed twice in one second gets a suffix
+    let mut id = format!("{}-{}", deleted_at, name);
+    let mut suffix = 1;
+    let dir = loop {
+        let dir = root.join(&id);
+        match fs::create_dir(&dir) {
+            Ok(()) => break dir,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                suffix += 1;
+                id = format!("{}-{}-{}", deleted_at, name, suffix);
+            }
+            Err(e) => return Err(SkillsError::io(&dir, e)),
+        }
+    };
+
+    let link_target = if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path).map_err(|e| SkillsError::io(path, e))?;
+        Some(target.to_string_lossy().to_string())
+    } else {
+        None
+    };
+    let provenance = source_manager::take_entry(scope, agent, path).unwrap_or_else(|e| {
+        eprintln!("Failed to read provenance for {}: {}", path.display(), e);
+        None
+    });
+    let entry = TrashEntry {
+        id,
+        name,
+        agent: agent.to_string(),
+        scope: scope.clone(),
+        origin: path.to_string_lossy().to_string(),
+        deleted_at,
+        link_target,
+        provenance,
+    };
+
+    let result = write_entry(&dir, &entry).and_then(|_| match entry.link_target {
+        Some(_) => place::remove_entry(path).map_err(|e| SkillsError::io(path, e)),
+        None => move_dir(path, &dir.join(CONTENT_DIR)),
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_dir_all(&dir);
+        if let Some(provenance) = entry.provenance {
+            let _ = source_manager::restore_entry(provenance);
+        }
+        return Err(e);
+    }
+    Ok(entry)
+}
+
+fn write_entry(dir: &Path, entry: &TrashEntry) -> Result<()> {
+    let path = dir.join(ENTRY_FILE);
+    let content =
+        serde_json::to_string_pretty(entry).map_err(|e| SkillsError::Other(e.to_string()))?;
+    fs::write(&path, content).map_err(|e| SkillsError::io(&path, e))
+}
+
+/// Everything in the trash, most recently removed first.
+pub fn list() -> Result<Vec<TrashEntry>> {
+    if let Err(e) = purge_expired() {
+        eprintln!("Failed to purge expired trash: {}", e);
+    }
+    entries()
+}
+
+fn entries() -> Result<Vec<TrashEntry>> {
+    let root = trash_dir()?;
+    let Ok(dirs) = fs::read_dir(&root) else {
+        return Ok(Vec::new());
+    };
+    let mut entries: Vec<TrashEntry> = dirs
+        .flatten()
+        .filter_map(|dir| match read_entry(&dir.path()) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                eprintln!("Skipping trash entry: {}", e);
+                None
+            }
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    Ok(entries)
+}
+
+/// Puts a trashed skill back where it was removed from.
+pub fn restore(id: &str) -> Result<TrashEntry> {
+    let dir = entry_dir(id)?;
+    if !dir.is_dir() {
+        return Err(SkillsError::NotFound { path: dir });
+    }
+    let entry = read_entry(&dir)?;
+    let origin = PathBuf::from(&entry.origin);
+    if fs::symlink_metadata(&origin).is_ok() {
+        return Err(SkillsError::InvalidInput(format!(
+            "{} already exists; remove it before restoring",
+            origin.display()
+        )));
+    }
+    paths::ensure_parent(&origin)?;
+
+    match &entry.link_target {
+        Some(target) => {
+            place::link_dir(Path::new(target), &origin).map_err(|e| SkillsError::io(&origin, e))?
+        }
+        None => move_dir(&dir.join(CONTENT_DIR), &origin)?,
+    }
+    if let Some(provenance) = entry.provenance.clone() {
+        if let Err(e) = source_manager::restore_entry(provenance) {
+            eprintln!("Failed to restore provenance for {}: {}", entry.name, e);
+        }
+    }
+    fs::remove_dir_all(&dir).map_err(|e| SkillsError::io(&dir, e))?;
+    Ok(entry)
+}
+
+/// Deletes the given entries for good, or everything when `ids` is `None`.
+pub fn purge(ids: Option<&[String]>) -> Result<Vec<TrashEntry>> {
+    let mut purged = Vec::new();
+    for entry in entries()? {
+        if ids.is_some_and(|ids| !ids.contains(&entry.id)) {
+            continue;
+        }
+        let dir = entry_dir(&entry.id)?;
+        fs::remove_dir_all(&dir).map_err(|e| SkillsError::io(&dir, e))?;
+        purged.push(entry);
+    }
+    Ok(purged)
+}
+
+/// Deletes entries older than the retention setting.
+pub fn purge_expired() -> Result<Vec<TrashEntry>> {
+    let days = settings::trash_retention_days();
+    if days == 0 {
+        return Ok(Vec::new());
+    }
+    let cutoff = now_secs().saturating_sub(u64::from(days) * 24 * 60 * 60);
+    let expired: Vec<String> = entries()?
+        .into_iter()
+        .filter(|entry| entry.deleted_at < cutoff)
+        .map(|entry| entry.id)
+        .collect();
+    if expired.is_empty() {
+        return Ok(Vec::new());
+    }
+    purge(Some(&expired))
+}
+
+/// Renames `src` to `dest`, copying when they are on different file systems.
+fn move_dir(src: &Path, dest: &Path) -> Result<()> {
+    match fs::rename(src, dest) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_tree(src, dest)?;
+            fs::remove_dir_all(src).map_err(|e| SkillsError::io(src, e))
+        }
+        Err(e) => Err(SkillsError::io(src, e)),
+    }
+}
+
+/// Copies everything, `.git` and symlinks included, unlike
+/// [`place::copy_dir`] which is meant for installs.
+fn copy_tree(src: &Path, dest: &Path) -> Result<()> {
+    for entry in WalkDir::new(src) {
+        let entry = entry.map_err(|e| SkillsError::Io {
+            path: e.path().map(Path::to_path_buf),
+            message: e.to_string(),
+        })?;
+        let relative = entry.path().strip_prefix(src).unwrap_or(entry.path());
+        let target = dest.join(relative);
+        let result = if entry.path_is_symlink() {
+            fs::read_link(entry.path())
+                .and_then(|link| place::copy_link(&link, entry.path(), &target))
+        } else if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)
+        } else {
+            fs::copy(entry.path(), &target).map(|_| ())
+        };
+        result.map_err(|e| SkillsError::io(&target, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{sandbox, write_skill};
+
+    fn listed(id: &str) -> bool {
+        list().unwrap().iter().any(|entry| entry.id == id)
+    }
+
+    #[test]
+    fn restore_puts_a_skill_back() {
+        sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let skill = dir.path().join("skills/pdf");
+        write_skill(&skill, "pdf");
+        fs::write(skill.join("notes.md"), "local edits").unwrap();
+
+        let entry = move_to_trash(&skill, "claude-code", &SkillScope::Global).unwrap();
+        assert!(fs::symlink_metadata(&skill).is_err());
+        assert!(entry.link_target.is_none());
+        assert!(listed(&entry.id));
+
+        restore(&entry.id).unwrap();
+        assert_eq!(
+            fs::read_to_string(skill.join("notes.md")).unwrap(),
+            "local edits"
+        );
+        assert!(!listed(&entry.id));
+        assert!(matches!(
+            restore(&entry.id),
+            Err(SkillsError::NotFound { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restore_puts_a_link_back() {
+        sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let copy = dir.path().join("store/pdf");
+        write_skill(&copy, "pdf");
+        let link = dir.path().join("skills/pdf");
+        fs::create_dir_all(link.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&copy, &link).unwrap();
+
+        let entry = move_to_trash(&link, "claude-code", &SkillScope::Global).unwrap();
+        assert_eq!(entry.link_target.as_deref(), Some(&*copy.to_string_lossy()));
+        // Only the link goes
+        assert!(copy.join("SKILL.md").is_file());
+
+        restore(&entry.id).unwrap();
+        assert_eq!(fs::read_link(&link).unwrap(), copy);
+    }
+
+    #[test]
+    fn restore_refuses_to_overwrite() {
+        sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let skill = dir.path().join("pdf");
+        write_skill(&skill, "pdf");
+
+        let entry = move_to_trash(&skill, "claude-code", &SkillScope::Global).unwrap();
+        write_skill(&skill, "pdf");
+        assert!(matches!(
+            restore(&entry.id),
+            Err(SkillsError::InvalidInput(_))
+        ));
+        assert!(listed(&entry.id));
+        purge(Some(&[entry.id])).unwrap();
+    }
+
+    #[test]
+    fn purge_deletes_only_the_given_entries() {
+        sandbox();
+        let dir = tempfile::tempdir().unwrap();
+        let mut ids = Vec::new();
+        for name in ["pdf", "docx"] {
+            let skill = dir.path().join(name);
+            write_skill(&skill, name);
+            ids.push(
+                move_to_trash(&skill, "claude-code", &SkillScope::Global)
+                    .unwrap()
+                    .id,
+            );
+        }
+
+        let purged = purge(Some(&ids[..1])).unwrap();
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].id, ids[0]);
+        assert!(!listed(&ids[0]));
+        assert!(listed(&ids[1]));
+        assert!(matches!(
+            restore(&ids[0]),
+            Err(SkillsError::NotFound { .. })
+        ));
+
+        restore(&ids[1]).unwrap();
+        assert!(dir.path().join("docx/SKILL.md").is_file());
+    }
+
+    #[test]
+    fn rejects_ids_that_are_paths() {
+        for id in ["", "..", "../x", "a/b"] {
+            assert!(matches!(restore(id), Err(SkillsError::InvalidInput(_))));
+        }
+    }
+}
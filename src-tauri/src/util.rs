@@ -0,0 +1,86 @@
+//! Small helpers shared by the modules that keep state on disk.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Result, SkillsError};
+
+/// Seconds since the Unix epoch.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Takes a cross-process lock on `path`, creating it if needed. Released
+/// when the returned file is dropped.
+pub fn lock(path: &Path, exclusive: bool) -> Result<File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| SkillsError::io(dir, e))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| SkillsError::io(path, e))?;
+    let locked = if exclusive {
+        file.lock()
+    } else {
+        file.lock_shared()
+    };
+    locked.map_err(|e| SkillsError::io(path, e))?;
+    Ok(file)
+}
+
+/// Writes next to `path`, syncs, and renames over it, so readers never see
+/// half a file and a crash never leaves a torn one.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    write_with(path, content, false)
+}
+
+/// Like [`write_atomic`], but only the user may read the file, from the
+/// moment it is created.
+pub fn write_private(path: &Path, content: &[u8]) -> Result<()> {
+    write_with(path, content, true)
+}
+
+fn write_with(path: &Path, content: &[u8], private: bool) -> Result<()> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut tmp = tempfile::NamedTempFile::new_in(dir).map_err(|e| SkillsError::io(dir, e))?;
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::PermissionsExt;
+        tmp.as_file()
+            .set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|e| SkillsError::io(tmp.path(), e))?;
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    tmp.write_all(content)
+        .and_then(|_| tmp.as_file().sync_all())
+        .map_err(|e| SkillsError::io(tmp.path(), e))?;
+    tmp.persist(path)
+        .map_err(|e| SkillsError::io(path, e.error))?;
+    Ok(())
+}
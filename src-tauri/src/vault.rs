@@ -0,0 +1,390 @@
+//! Encrypted local store for secret skill config values.
+//!
+//! Values a skill's config schema marks as secret are sealed into
+//! `<data dir>/vault.json` with XChaCha20-Poly1305, and `skill.config.json`
+//! only keeps a `vault:<id>` reference to them. The key is derived from a
+//! passphrase with Argon2id. The app keeps it in memory once unlocked; the
+//! CLI and other headless callers pass the passphrase in
+//! [`PASSPHRASE_ENV`].
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+
+use crate::error::{Result, SkillsError};
+use crate::paths;
+use crate::util::{self, from_hex, now_secs, to_hex};
+
+pub const PASSPHRASE_ENV: &str = "SKILLS_MANAGER_VAULT_PASSPHRASE";
+pub const REFERENCE_PREFIX: &str = "vault:";
+
+const VAULT_FILE: &str = "vault.json";
+const LOCK_FILE: &str = "vault.json.lock";
+const VAULT_VERSION: u32 = 1;
+/// Sealed with the key when the vault is created, so a wrong passphrase is
+/// caught before anything is encrypted with it.
+const CHECK_PLAINTEXT: &[u8] = b"skills-manager vault";
+
+/// The key of the unlocked vault, with the salt it was derived with.
+static UNLOCKED: Mutex<Option<(String, Key)>> = Mutex::new(None);
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Sealed {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct SecretEntry {
+    /// `skill.config.json` the secret belongs to
+    config: String,
+    /// JSON pointer of the value in that config
+    pointer: String,
+    /// Seconds since the Unix epoch
+    updated_at: u64,
+    #[serde(flatten)]
+    sealed: Sealed,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    check: Sealed,
+    #[serde(default)]
+    secrets: BTreeMap<String, SecretEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VaultStatus {
+    pub path: String,
+    /// False until the first secret is stored or the vault is unlocked
+    pub exists: bool,
+    pub unlocked: bool,
+    pub secrets: usize,
+}
+
+/// The vault file, loaded for reading or changing its secrets.
+pub struct Vault {
+    path: PathBuf,
+    file: Option<VaultFile>,
+    /// Changes since loading, applied to the file as it is on disk when
+    /// saving, so secrets another process stored meanwhile are kept
+    added: BTreeMap<String, SecretEntry>,
+    removed: BTreeSet<String>,
+}
+
+fn vault_path() -> Result<PathBuf> {
+    Ok(paths::data_dir()?.join(VAULT_FILE))
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0; len];
+    OsRng.fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+/// The id in a `vault:<id>` reference.
+pub fn reference_id(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(REFERENCE_PREFIX)
+        .filter(|id| !id.is_empty())
+}
+
+fn read(path: &Path) -> Result<Option<VaultFile>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| SkillsError::invalid_config(path, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(SkillsError::io(path, e)),
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &str) -> Result<Key> {
+    let salt =
+        from_hex(salt).ok_or_else(|| SkillsError::Other("Corrupt vault salt".to_string()))?;
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| SkillsError::Other(format!("Failed to derive vault key: {}", e)))?;
+    Ok(key)
+}
+
+fn seal(key: &Key, plaintext: &[u8]) -> Result<Sealed> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| SkillsError::Other("Failed to encrypt secret".to_string()))?;
+    Ok(Sealed {
+        nonce: to_hex(&nonce),
+        ciphertext: to_hex(&ciphertext),
+    })
+}
+
+fn open(key: &Key, sealed: &Sealed) -> Option<Vec<u8>> {
+    let nonce = from_hex(&sealed.nonce).filter(|n| n.len() == 24)?;
+    let ciphertext = from_hex(&sealed.ciphertext)?;
+    XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .ok()
+}
+
+fn locked() -> SkillsError {
+    SkillsError::VaultLocked {
+        env: PASSPHRASE_ENV.to_string(),
+    }
+}
+
+impl Vault {
+    pub fn load() -> Result<Self> {
+        let path = vault_path()?;
+        let file = read(&path)?;
+        Ok(Vault {
+            path,
+            file,
+            added: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        })
+    }
+
+    /// The key from an earlier [`unlock`], else from [`PASSPHRASE_ENV`].
+    /// Creates the vault with the passphrase from the environment if there
+    /// is none yet.
+    fn key(&mut self) -> Result<Key> {
+        let remembered = UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let (Some(file), Some((salt, key))) = (&self.file, &remembered) {
+            if &file.salt == salt {
+                return Ok(*key);
+            }
+        }
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .ok()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(locked)?;
+        self.unlock_with(&passphrase)
+    }
+
+    fn unlock_with(&mut self, passphrase: &str) -> Result<Key> {
+        let (salt, key) = match &self.file {
+            Some(file) => {
+                let key = derive_key(passphrase, &file.salt)?;
+                if open(&key, &file.check).as_deref() != Some(CHECK_PLAINTEXT) {
+                    return Err(SkillsError::InvalidInput(
+                        "Wrong vault passphrase".to_string(),
+                    ));
+                }
+                (file.salt.clone(), key)
+            }
+            None => {
+                let salt = random_hex(16);
+                let key = derive_key(passphrase, &salt)?;
+                self.file = Some(VaultFile {
+                    version: VAULT_VERSION,
+                    salt: salt.clone(),
+                    check: seal(&key, CHECK_PLAINTEXT)?,
+                    secrets: BTreeMap::new(),
+                });
+                (salt, key)
+            }
+        };
+        *UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()) = Some((salt, key));
+        Ok(key)
+    }
+
+    /// Seals `secret` for the value at `pointer` in `config` and returns its
+    /// reference. Every value gets a new id, so earlier versions of the
+    /// config keep resolving to the secret they were saved with.
+    pub fn put(&mut self, config: &Path, pointer: &str, secret: &str) -> Result<String> {
+        let key = self.key()?;
+        let config = config.to_string_lossy().to_string();
+        let file = self.file.as_mut().ok_or_else(locked)?;
+        let id = random_hex(12);
+        let entry = SecretEntry {
+            config,
+            pointer: pointer.to_string(),
+            updated_at: now_secs(),
+            sealed: seal(&key, secret.as_bytes())?,
+        };
+        file.secrets.insert(id.clone(), entry.clone());
+        self.removed.remove(&id);
+        self.added.insert(id.clone(), entry);
+        Ok(format!("{}{}", REFERENCE_PREFIX, id))
+    }
+
+    /// Whether the vault holds the secret of `id`.
+    pub fn contains(&self, id: &str) -> bool {
+        self.file
+            .as_ref()
+            .is_some_and(|file| file.secrets.contains_key(id))
+    }
+
+    /// The secret a `vault:<id>` reference stands for.
+    pub fn get(&mut self, reference: &str) -> Result<String> {
+        let id = reference_id(reference).ok_or_else(|| {
+            SkillsError::InvalidInput(format!("Not a vault reference: {}", reference))
+        })?;
+        let key = self.key()?;
+        let entry = self
+            .file
+            .as_ref()
+            .and_then(|file| file.secrets.get(id))
+            .ok_or_else(|| SkillsError::NotFound {
+                path: PathBuf::from(reference),
+            })?;
+        open(&key, &entry.sealed)
+            .and_then(|plain| String::from_utf8(plain).ok())
+            .ok_or_else(|| SkillsError::Other(format!("Failed to decrypt {}", reference)))
+    }
+
+    /// Drops the secrets of `config` other than `keep`, e.g. those no saved
+    /// version refers to any more. Returns whether any were dropped.
+    pub fn retain(&mut self, config: &Path, keep: &[&str]) -> bool {
+        let Some(file) = self.file.as_mut() else {
+            return false;
+        };
+        let config = config.to_string_lossy();
+        let dropped: Vec<String> = file
+            .secrets
+            .iter()
+            .filter(|(id, entry)| entry.config == config && !keep.contains(&id.as_str()))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in &dropped {
+            file.secrets.remove(id);
+            self.added.remove(id);
+        }
+        self.removed.extend(dropped.iter().cloned());
+        !dropped.is_empty()
+    }
+
+    /// Writes the changes made since [`Vault::load`] into the vault file as
+    /// it is now, under a lock shared with other processes.
+    pub fn save(&mut self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let _lock = util::lock(&self.path.with_file_name(LOCK_FILE), true)?;
+        let merged = match read(&self.path)? {
+            None => file.clone(),
+            Some(mut current) if current.salt == file.salt => {
+                current.secrets.retain(|id, _| !self.removed.contains(id));
+                current.secrets.extend(self.added.clone());
+                current
+            }
+            Some(_) => {
+                return Err(SkillsError::Other(format!(
+                    "{} was recreated with another passphrase; unlock it again",
+                    self.path.display()
+                )))
+            }
+        };
+        let content =
+            serde_json::to_string_pretty(&merged).map_err(|e| SkillsError::Other(e.to_string()))?;
+        util::write_private(&self.path, content.as_bytes())?;
+        self.file = Some(merged);
+        self.added.clear();
+        self.removed.clear();
+        Ok(())
+    }
+
+    fn status(&self) -> VaultStatus {
+        let unlocked = UNLOCKED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .is_some_and(|(salt, _)| self.file.as_ref().is_none_or(|f| &f.salt == salt));
+        VaultStatus {
+            path: self.path.to_string_lossy().to_string(),
+            exists: self.file.is_some(),
+            unlocked,
+            secrets: self.file.as_ref().map_or(0, |f| f.secrets.len()),
+        }
+    }
+}
+
+pub fn status() -> Result<VaultStatus> {
+    Ok(Vault::load()?.status())
+}
+
+/// Checks `passphrase` and keeps the key in memory until [`lock`]. Creates
+/// the vault, with `passphrase` as its passphrase, if there is none yet.
+pub fn unlock(passphrase: &str) -> Result<VaultStatus> {
+    if passphrase.is_empty() {
+        return Err(SkillsError::InvalidInput(
+            "The vault passphrase can't be empty".to_string(),
+        ));
+    }
+    let mut vault = Vault::load()?;
+    let created = vault.file.is_none();
+    vault.unlock_with(passphrase)?;
+    if created {
+        vault.save()?;
+    }
+    Ok(vault.status())
+}
+
+/// Forgets the key of an unlocked vault.
+pub fn lock() {
+    *UNLOCKED.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_at(path: &Path) -> Vault {
+        Vault {
+            path: path.to_path_buf(),
+            file: read(path).unwrap(),
+            added: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn seal_and_open() {
+        let key = derive_key("passphrase", &random_hex(16)).unwrap();
+        let sealed = seal(&key, b"token").unwrap();
+        assert_eq!(open(&key, &sealed).as_deref(), Some(&b"token"[..]));
+
+        let other = derive_key("other", &random_hex(16)).unwrap();
+        assert_eq!(open(&other, &sealed), None);
+    }
+
+    #[test]
+    fn round_trip_through_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(VAULT_FILE);
+        let config = dir.path().join("skill.config.json");
+
+        let mut vault = vault_at(&path);
+        vault.unlock_with("correct horse").unwrap();
+        let first = vault.put(&config, "/token", "one").unwrap();
+        let second = vault.put(&config, "/token", "two").unwrap();
+        assert_ne!(first, second);
+        vault.save().unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let mut reloaded = vault_at(&path);
+        assert!(matches!(
+            reloaded.unlock_with("wrong"),
+            Err(SkillsError::InvalidInput(_))
+        ));
+        reloaded.unlock_with("correct horse").unwrap();
+        assert_eq!(reloaded.get(&first).unwrap(), "one");
+        assert_eq!(reloaded.get(&second).unwrap(), "two");
+        assert!(reloaded.get("vault:missing").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Tamper detection: compares installed skills with the file digests the
+//! provenance store recorded when they were installed.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::digest;
+use crate::error::Result;
+use crate::scope::SkillScope;
+use crate::skills;
+use crate::source_manager::{ProvenanceEntry, SourceIndex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    /// Same files and contents as at install time
+    Intact,
+    Modified,
+    /// Installed before digests were recorded, or not by this app
+    Unknown,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SkillVerification {
+    pub id: String,
+    pub agent: String,
+    pub scope: SkillScope,
+    pub path: String,
+    pub status: IntegrityStatus,
+    /// Files whose content changed
+    pub modified: Vec<String>,
+    /// Files that weren't there at install time
+    pub added: Vec<String>,
+    /// Files that were there at install time and are gone
+    pub deleted: Vec<String>,
+}
+
+/// Checks every installed skill, in the home directory and registered
+/// workspaces.
+pub fn verify_skills() -> Result<Vec<SkillVerification>> {
+    let index = SourceIndex::load()?;
+    let mut results = Vec::new();
+    for skill in skills::local_skills()? {
+        let path = Path::new(&skill.path);
+        let entry = index.entry(&skill.scope, &skill.agent, path);
+        let mut result = SkillVerification {
+            id: skill.id.clone(),
+            agent: skill.agent.clone(),
+            scope: skill.scope.clone(),
+            path: skill.path.clone(),
+            status: IntegrityStatus::Unknown,
+            modified: Vec::new(),
+            added: Vec::new(),
+            deleted: Vec::new(),
+        };
+        if let Some(entry) = entry {
+            compare(entry, path, &mut result)?;
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+fn compare(entry: &ProvenanceEntry, path: &Path, result: &mut SkillVerification) -> Result<()> {
+    if entry.files.is_empty() {
+        // Older entries only have the whole-directory digest
+        if let Some(expected) = &entry.content_hash {
+            result.status = if &digest::dir_digest(path)? == expected {
+                IntegrityStatus::Intact
+            } else {
+                IntegrityStatus::Modified
+            };
+        }
+        return Ok(());
+    }
+
+    let current = digest::file_digests(path)?;
+    let (modified, added, deleted) = diff(&entry.files, &current);
+    result.status = if modified.is_empty() && added.is_empty() && deleted.is_empty() {
+        IntegrityStatus::Intact
+    } else {
+        IntegrityStatus::Modified
+    };
+    result.modified = modified;
+    result.added = added;
+    result.deleted = deleted;
+    Ok(())
+}
+
+/// Files modified, added and deleted going from `before` to `after`.
+pub(crate) fn diff(
+    before: &BTreeMap<String, String>,
+    after: &BTreeMap<String, String>,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let modified = before
+        .iter()
+        .filter(|(file, hash)| after.get(*file).is_some_and(|h| h != *hash))
+        .map(|(file, _)| file.clone())
+        .collect();
+    let added = after
+        .keys()
+        .filter(|file| !before.contains_key(*file))
+        .cloned()
+        .collect();
+    let deleted = before
+        .keys()
+        .filter(|file| !after.contains_key(*file))
+        .cloned()
+        .collect();
+    (modified, added, deleted)
+}
@@ -0,0 +1,321 @@
+//! Watches agent skill directories and project roots for changes made
+//! outside the app (a `git pull`, the CLI, an agent editing SKILL.md) and
+//! reports the affected skills once things have settled.
+//!
+//! Existing skills directories are watched recursively. One that doesn't
+//! exist yet is covered by a non-recursive watch on its nearest existing
+//! parent, and is picked up once it appears.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::error::{Result, SkillsError};
+use crate::paths;
+use crate::scanner::{self, ScanDir};
+use crate::scope::SkillScope;
+use crate::skills::{self, Skill};
+use crate::workspaces;
+
+pub const CHANGED_EVENT: &str = "skills://changed";
+
+/// How long a directory has to be quiet before its changes are reported.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Report at least this often while changes keep coming.
+const MAX_DELAY: Duration = Duration::from_secs(3);
+
+/// A skill that was added, changed or removed.
+#[derive(Debug, serde::Serialize)]
+pub struct ChangedSkill {
+    pub id: String,
+    pub agent: String,
+    pub scope: SkillScope,
+    pub path: String,
+    /// The skill as it is now; `None` once it is gone
+    pub skill: Option<Skill>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SkillsChanged {
+    pub skills: Vec<ChangedSkill>,
+}
+
+/// Where change notifications go.
+pub trait ChangeSink: Send + Sync {
+    fn emit(&self, event: &SkillsChanged);
+
+    /// A problem that doesn't stop the watcher, such as a directory that
+    /// couldn't be watched.
+    fn error(&self, message: &str) {
+        eprintln!("Skills watcher: {}", message);
+    }
+}
+
+/// Stops watching when dropped.
+pub struct SkillsWatcher {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    watcher: Mutex<RecommendedWatcher>,
+    state: Mutex<WatchState>,
+    sink: Arc<dyn ChangeSink>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    dirs: Vec<ScanDir>,
+    watched: BTreeMap<PathBuf, RecursiveMode>,
+}
+
+impl SkillsWatcher {
+    pub fn start(sink: Arc<dyn ChangeSink>) -> Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        let errors = sink.clone();
+        let watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if is_change(&event.kind) => {
+                    let _ = tx.send(event.paths);
+                }
+                Ok(_) => {}
+                Err(e) => errors.error(&e.to_string()),
+            })
+            .map_err(|e| SkillsError::Other(format!("Failed to start watcher: {}", e)))?;
+
+        let inner = Arc::new(Inner {
+            watcher: Mutex::new(watcher),
+            state: Mutex::new(WatchState::default()),
+            sink,
+        });
+        inner.refresh()?;
+
+        let weak = Arc::downgrade(&inner);
+        thread::Builder::new()
+            .name("skills-watcher".to_string())
+            .spawn(move || debounce(rx, weak))
+            .map_err(|e| SkillsError::Other(format!("Failed to start watcher: {}", e)))?;
+        Ok(SkillsWatcher { inner })
+    }
+
+    /// Re-reads the agents and workspaces to watch. Call after either changes.
+    pub fn refresh(&self) -> Result<()> {
+        self.inner.refresh().map(|_| ())
+    }
+}
+
+/// Reading a file (our own scans included) isn't a change.
+fn is_change(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) => false,
+        _ => true,
+    }
+}
+
+/// Collects paths until nothing has changed for [`DEBOUNCE`], then reports.
+/// Ends when the watcher is dropped.
+fn debounce(rx: Receiver<Vec<PathBuf>>, inner: Weak<Inner>) {
+    while let Ok(first) = rx.recv() {
+        let mut changed: BTreeSet<PathBuf> = first.into_iter().collect();
+        let started = Instant::now();
+        while started.elapsed() < MAX_DELAY {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(paths) => changed.extend(paths),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        inner.report(&changed);
+    }
+}
+
+impl Inner {
+    /// Updates the watches and returns skills directories that are newly
+    /// watched, e.g. because they were just created.
+    fn refresh(&self) -> Result<Vec<ScanDir>> {
+        let dirs = skills::local_scan_dirs()?;
+        let home = paths::home_dir()?;
+        let roots = workspaces::list_workspaces()?;
+
+        let mut wanted = BTreeMap::new();
+        for root in &roots {
+            wanted.insert(root.clone(), RecursiveMode::NonRecursive);
+        }
+        for dir in &dirs {
+            if dir.dir.is_dir() {
+                wanted.insert(dir.dir.clone(), RecursiveMode::Recursive);
+                continue;
+            }
+            let root = dir
+                .scope
+                .project_path()
+                .map(PathBuf::from)
+                .unwrap_or(home.clone());
+            if let Some(parent) = existing_parent(&dir.dir, &root) {
+                wanted.entry(parent).or_insert(RecursiveMode::NonRecursive);
+            }
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut watcher = self.watcher.lock().unwrap_or_else(|e| e.into_inner());
+        let stale: Vec<PathBuf> = state
+            .watched
+            .iter()
+            .filter(|(path, mode)| wanted.get(*path) != Some(*mode))
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in stale {
+            let _ = watcher.unwatch(&path);
+            state.watched.remove(&path);
+        }
+
+        let mut appeared = Vec::new();
+        for (path, mode) in wanted {
+            if state.watched.contains_key(&path) {
+                continue;
+            }
+            match watcher.watch(&path, mode) {
+                Ok(()) => {
+                    if mode == RecursiveMode::Recursive {
+                        appeared.extend(dirs.iter().filter(|d| d.dir == path).cloned());
+                    }
+                    state.watched.insert(path, mode);
+                }
+                Err(e) => self
+                    .sink
+                    .error(&format!("Failed to watch {}: {}", path.display(), e)),
+            }
+        }
+        // Everything is new on the first call; only report later arrivals
+        if state.dirs.is_empty() {
+            appeared.clear();
+        }
+        state.dirs = dirs;
+        Ok(appeared)
+    }
+
+    fn report(&self, changed: &BTreeSet<PathBuf>) {
+        let dirs = self
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .dirs
+            .clone();
+
+        // Each changed skill, by its directory and id, with the directory
+        // it was found in, and whether a watched directory itself or one of
+        // its parents changed. Directories are kept rather than indices,
+        // since a refresh replaces the list.
+        let mut touched = BTreeMap::new();
+        let mut layout_changed = false;
+        for path in changed {
+            let owner = dirs
+                .iter()
+                .filter(|dir| path.starts_with(&dir.dir) && path != &dir.dir)
+                .max_by_key(|dir| dir.dir.components().count());
+            match owner {
+                Some(dir) => {
+                    let rel = path.strip_prefix(&dir.dir).unwrap_or(path);
+                    if let Some(id) = rel.components().next() {
+                        let id = id.as_os_str().to_string_lossy().to_string();
+                        touched.insert((dir.dir.clone(), id), dir.clone());
+                    }
+                }
+                // Something on the way to a skills directory, e.g. `.claude`
+                // being created
+                None if dirs.iter().any(|dir| dir.dir.starts_with(path)) => {
+                    layout_changed = true;
+                }
+                None => {}
+            }
+        }
+
+        let mut appeared = Vec::new();
+        let mut dirs = dirs;
+        if layout_changed {
+            match self.refresh() {
+                Ok(new_dirs) => appeared = new_dirs,
+                Err(e) => self
+                    .sink
+                    .error(&format!("Failed to update watched directories: {}", e)),
+            }
+            dirs = self
+                .state
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .dirs
+                .clone();
+        }
+
+        let sources = skills::load_source_index();
+        let mut skills = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut add = |dir: &ScanDir, path: PathBuf, skills: &mut Vec<ChangedSkill>| {
+            if !seen.insert(path.clone()) {
+                return;
+            }
+            scanner::invalidate(&path);
+            let skill = scanner::scan_skill(dir, &path, &sources);
+            // Stray files in a skills directory aren't skills
+            if skill.is_none() && path.is_file() {
+                return;
+            }
+            skills.push(ChangedSkill {
+                id: path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                agent: dir.agent.clone(),
+                scope: dir.scope.clone(),
+                path: path.to_string_lossy().to_string(),
+                skill,
+            });
+        };
+
+        for ((_, id), dir) in touched {
+            let path = dir.dir.join(&id);
+            // Agents that link to a changed skill see the change too
+            let target = path.canonicalize().ok();
+            add(&dir, path, &mut skills);
+            if let Some(target) = target {
+                for other in &dirs {
+                    let link = other.dir.join(&id);
+                    if link.is_symlink() && link.canonicalize().ok().as_ref() == Some(&target) {
+                        add(other, link, &mut skills);
+                    }
+                }
+            }
+        }
+        for dir in appeared {
+            let Ok(entries) = std::fs::read_dir(&dir.dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                add(&dir, entry.path(), &mut skills);
+            }
+        }
+
+        if !skills.is_empty() {
+            self.sink.emit(&SkillsChanged { skills });
+        }
+    }
+}
+
+/// The closest existing parent of `dir`, not going above `root`.
+fn existing_parent(dir: &Path, root: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .skip(1)
+        .take_while(|parent| parent.starts_with(root))
+        .find(|parent| parent.is_dir())
+        .map(Path::to_path_buf)
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{Result, SkillsError};
+use crate::paths;
+use crate::scope;
+use crate::util;
+
+const WORKSPACES_FILE: &str = "workspaces.json";
+
+/// Project roots whose agent directories are scanned alongside the home directory.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct Workspaces {
+    #[serde(default)]
+    roots: Vec<PathBuf>,
+}
+
+fn workspaces_file_path() -> Result<PathBuf> {
+    paths::config_dir()
+        .map(|dir| dir.join(WORKSPACES_FILE))
+        .ok_or_else(|| SkillsError::Other("Could not find config directory".to_string()))
+}
+
+fn read_workspaces() -> Result<Workspaces> {
+    let path = workspaces_file_path()?;
+    if !path.exists() {
+        return Ok(Workspaces::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| SkillsError::io(&path, e))?;
+    serde_json::from_str(&content).map_err(|e| SkillsError::invalid_config(&path, e))
+}
+
+fn write_workspaces(workspaces: &Workspaces) -> Result<()> {
+    let path = workspaces_file_path()?;
+    paths::ensure_parent(&path)?;
+    let content =
+        serde_json::to_string_pretty(workspaces).map_err(|e| SkillsError::Other(e.to_string()))?;
+    util::write_atomic(&path, content.as_bytes())
+}
+
+pub fn list_workspaces() -> Result<Vec<PathBuf>> {
+    Ok(read_workspaces()?.roots)
+}
+
+pub fn add_workspace(path: &str) -> Result<PathBuf> {
+    let root = scope::project_root(path)?;
+    let mut workspaces = read_workspaces()?;
+    if !workspaces.roots.contains(&root) {
+        workspaces.roots.push(root.clone());
+        write_workspaces(&workspaces)?;
+    }
+    Ok(root)
+}
+
+pub fn remove_workspace(path: &str) -> Result<()> {
+    let mut workspaces = read_workspaces()?;
+    let target = PathBuf::from(path);
+    // The directory may already be gone, so also match the raw path
+    let canonical = target.canonicalize().unwrap_or_else(|_| target.clone());
+    workspaces
+        .roots
+        .retain(|root| root != &target && root != &canonical);
+    write_workspaces(&workspaces)
+}
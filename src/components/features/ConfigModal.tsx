@@ -1,11 +1,12 @@
 import { useState, useEffect } from "react";
-import { X, Save, AlertCircle, BookOpen, Info } from "lucide-react";
+import { X, Save, AlertCircle, BookOpen, Info, Lock, History, RotateCcw } from "lucide-react";
 import { invoke } from "@tauri-apps/api/core";
-import { cn } from "../../lib/utils";
+import { cn, errorMessage, isSkillsError } from "../../lib/utils";
 
 interface ConfigModalProps {
     skillId: string;
     agent: string;
+    project?: string | null;
     isOpen: boolean;
     onClose: () => void;
 }
@@ -13,13 +14,39 @@ interface ConfigModalProps {
 interface SkillConfigResponse {
     current_config: string;
     documentation: string | null;
+    schema: object | null;
 }
 
-export function ConfigModal({ skillId, agent, isOpen, onClose }: ConfigModalProps) {
+interface ConfigVersion {
+    version: number;
+    saved_at: number;
+    content: string;
+}
+
+interface ConfigChange {
+    pointer: string;
+    kind: "added" | "removed" | "changed";
+    old: unknown;
+    new: unknown;
+}
+
+interface FieldError {
+    pointer: string;
+    message: string;
+}
+
+export function ConfigModal({ skillId, agent, project = null, isOpen, onClose }: ConfigModalProps) {
     const [config, setConfig] = useState("");
     const [documentation, setDocumentation] = useState<string | null>(null);
     const [loading, setLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
+    const [fieldErrors, setFieldErrors] = useState<FieldError[]>([]);
+    const [hasSchema, setHasSchema] = useState(false);
+    const [vaultLocked, setVaultLocked] = useState(false);
+    const [passphrase, setPassphrase] = useState("");
+    const [history, setHistory] = useState<ConfigVersion[]>([]);
+    const [showHistory, setShowHistory] = useState(false);
+    const [diff, setDiff] = useState<{ from: number, to: number, changes: ConfigChange[] } | null>(null);
     const [saveSuccess, setSaveSuccess] = useState(false);
     const [showDoc, setShowDoc] = useState(true);
 
@@ -27,18 +54,48 @@ export function ConfigModal({ skillId, agent, isOpen, onClose }: ConfigModalProp
         if (isOpen) {
             setLoading(true);
             setError(null);
-            invoke<SkillConfigResponse>("get_skill_config", { id: skillId, agent })
+            setFieldErrors([]);
+            setVaultLocked(false);
+            setShowHistory(false);
+            setDiff(null);
+            invoke<SkillConfigResponse>("get_skill_config", { id: skillId, agent, project })
                 .then((res) => {
                     setConfig(res.current_config);
                     setDocumentation(res.documentation);
+                    setHasSchema(res.schema !== null);
                     // If no documentation is found, maybe hide the doc area by default
                     if (!res.documentation) setShowDoc(false);
                     else setShowDoc(true);
                 })
-                .catch(err => setError(err.toString()))
+                .catch(err => setError(errorMessage(err)))
                 .finally(() => setLoading(false));
         }
-    }, [isOpen, skillId, agent]);
+    }, [isOpen, skillId, agent, project]);
+
+    const loadHistory = () => {
+        invoke<ConfigVersion[]>("list_config_history", { id: skillId, agent, project })
+            .then(setHistory)
+            .catch(err => setError(errorMessage(err)));
+    };
+
+    const handleDiff = (from: number, to: number) => {
+        invoke<ConfigChange[]>("diff_config_versions", { id: skillId, agent, from, to, project })
+            .then(changes => setDiff({ from, to, changes }))
+            .catch(err => setError(errorMessage(err)));
+    };
+
+    const handleRestore = (version: number) => {
+        setLoading(true);
+        setError(null);
+        invoke<ConfigVersion>("restore_config_version", { id: skillId, agent, version, project })
+            .then((saved) => {
+                setConfig(saved.content);
+                setDiff(null);
+                loadHistory();
+            })
+            .catch(err => setError(errorMessage(err)))
+            .finally(() => setLoading(false));
+    };
 
     const handleSave = () => {
         try {
@@ -47,21 +104,49 @@ export function ConfigModal({ skillId, agent, isOpen, onClose }: ConfigModalProp
 
             setLoading(true);
             setError(null);
-            invoke("save_skill_config", { id: skillId, agent, config })
+            setFieldErrors([]);
+            setVaultLocked(false);
+            invoke("save_skill_config", { id: skillId, agent, config, project })
                 .then(() => {
                     setSaveSuccess(true);
                     setTimeout(() => setSaveSuccess(false), 2000);
+                    if (showHistory) loadHistory();
+                })
+                .catch(err => {
+                    if (isSkillsError(err) && err.kind === "invalid_skill_config") {
+                        setFieldErrors(err.context.errors ?? []);
+                        setError("配置不符合 Schema (Config doesn't match the schema)");
+                    } else if (isSkillsError(err) && err.kind === "vault_locked") {
+                        // Secret fields go to the vault; ask for its passphrase
+                        setVaultLocked(true);
+                        setError(errorMessage(err));
+                    } else {
+                        setError(errorMessage(err));
+                    }
                 })
-                .catch(err => setError(err.toString()))
                 .finally(() => setLoading(false));
         } catch (e) {
             setError("Invalid JSON format");
         }
     };
 
+    const handleUnlock = () => {
+        setLoading(true);
+        invoke("unlock_vault", { passphrase })
+            .then(() => {
+                setPassphrase("");
+                setLoading(false);
+                handleSave();
+            })
+            .catch(err => {
+                setError(errorMessage(err));
+                setLoading(false);
+            });
+    };
+
     if (!isOpen) return null;
 
-    const noContent = !documentation && (config === "{}" || !config);
+    const noContent = !documentation && !hasSchema && (config === "{}" || !config);
 
     return (
         <div className="fixed inset-0 z-50 flex items-center justify-center p-4 bg-slate-900/40 backdrop-blur-sm animate-in fade-in duration-200">
@@ -83,6 +168,20 @@ export function ConfigModal({ skillId, agent, isOpen, onClose }: ConfigModalProp
                         </div>
                     </div>
                     <div className="flex items-center gap-2">
+                        <button
+                            onClick={() => {
+                                if (!showHistory) loadHistory();
+                                setShowHistory(!showHistory);
+                                setDiff(null);
+                            }}
+                            className={cn(
+                                "px-3 py-1.5 rounded-lg text-xs font-bold transition-all flex items-center gap-2",
+                                showHistory ? "bg-blue-600 text-white" : "bg-white border border-slate-200 text-slate-600 hover:bg-slate-50"
+                            )}
+                        >
+                            <History size={14} />
+                            历史版本
+                        </button>
                         {documentation && (
                             <button
                                 onClick={() => setShowDoc(!showDoc)}
@@ -134,7 +233,10 @@ export function ConfigModal({ skillId, agent, isOpen, onClose }: ConfigModalProp
                         ) : (
                             <div className="flex flex-col h-full">
                                 <label className="block text-xs font-bold text-slate-400 uppercase tracking-widest mb-2 flex items-center justify-between">
-                                    <span>skill.config.json</span>
+                                    <span>
+                                        skill.config.json
+                                        {hasSchema && <span className="ml-2 normal-case tracking-normal font-medium text-emerald-600">按 Schema 校验 (validated against schema)</span>}
+                                    </span>
                                     {error === "Invalid JSON format" && <span className="text-red-500 font-bold animate-pulse">格式错误</span>}
                                 </label>
                                 <textarea
@@ -156,13 +258,99 @@ export function ConfigModal({ skillId, agent, isOpen, onClose }: ConfigModalProp
                                 <span className="text-xs font-bold truncate">{error}</span>
                             </div>
                         )}
+
+                        {showHistory && (
+                            <div className="mt-3 bg-white border border-slate-200 rounded-lg p-3 text-xs text-slate-600 space-y-2 max-h-56 overflow-y-auto">
+                                {history.length === 0 ? (
+                                    <p className="text-slate-400">还没有保存过的版本 (No saved versions yet)</p>
+                                ) : history.map((v, i) => (
+                                    <div key={v.version} className="flex items-center justify-between gap-2">
+                                        <span className="font-mono">
+                                            v{v.version}
+                                            <span className="ml-2 text-slate-400">{new Date(v.saved_at * 1000).toLocaleString()}</span>
+                                            {i === 0 && <span className="ml-2 text-emerald-600 font-bold">当前</span>}
+                                        </span>
+                                        {i > 0 && (
+                                            <span className="flex items-center gap-2">
+                                                <button
+                                                    onClick={() => handleDiff(v.version, history[0].version)}
+                                                    className="text-blue-600 hover:underline font-bold"
+                                                >
+                                                    对比当前
+                                                </button>
+                                                <button
+                                                    onClick={() => handleRestore(v.version)}
+                                                    disabled={loading}
+                                                    className="flex items-center gap-1 text-slate-600 hover:text-blue-600 font-bold"
+                                                >
+                                                    <RotateCcw size={12} />
+                                                    恢复
+                                                </button>
+                                            </span>
+                                        )}
+                                    </div>
+                                ))}
+                                {diff && (
+                                    <div className="border-t border-slate-100 pt-2 space-y-1">
+                                        <p className="font-bold text-slate-500">v{diff.from} → v{diff.to}</p>
+                                        {diff.changes.length === 0 && <p className="text-slate-400">没有差异 (No changes)</p>}
+                                        {diff.changes.map((c, i) => (
+                                            <p key={i} className="font-mono break-all">
+                                                <span className={cn(
+                                                    "font-bold mr-2",
+                                                    c.kind === "added" ? "text-emerald-600" : c.kind === "removed" ? "text-red-500" : "text-amber-600"
+                                                )}>
+                                                    {c.kind === "added" ? "+" : c.kind === "removed" ? "-" : "~"}
+                                                </span>
+                                                {c.pointer || "(root)"}
+                                                {c.kind !== "added" && <span className="text-red-500 ml-2">{JSON.stringify(c.old)}</span>}
+                                                {c.kind === "changed" && " → "}
+                                                {c.kind !== "removed" && <span className="text-emerald-600 ml-1">{JSON.stringify(c.new)}</span>}
+                                            </p>
+                                        ))}
+                                    </div>
+                                )}
+                            </div>
+                        )}
+
+                        {vaultLocked && (
+                            <div className="mt-3 flex items-center gap-2">
+                                <Lock size={14} className="text-slate-400 shrink-0" />
+                                <input
+                                    type="password"
+                                    value={passphrase}
+                                    onChange={(e) => setPassphrase(e.target.value)}
+                                    onKeyDown={(e) => e.key === "Enter" && passphrase && handleUnlock()}
+                                    placeholder="密钥库口令 (Vault passphrase)"
+                                    className="flex-1 h-9 px-3 bg-slate-50 border border-slate-200 rounded-lg text-xs text-slate-700 focus:outline-none focus:border-blue-500 focus:bg-white transition-all"
+                                />
+                                <button
+                                    onClick={handleUnlock}
+                                    disabled={loading || !passphrase}
+                                    className="px-3 h-9 rounded-lg text-xs font-bold bg-blue-600 text-white hover:bg-blue-700 disabled:bg-slate-200 disabled:text-slate-400 transition-all"
+                                >
+                                    解锁并保存
+                                </button>
+                            </div>
+                        )}
+
+                        {fieldErrors.length > 0 && (
+                            <ul className="mt-2 max-h-32 overflow-y-auto space-y-1 text-xs text-red-600">
+                                {fieldErrors.map((e, i) => (
+                                    <li key={i} className="flex gap-2">
+                                        <span className="font-mono font-bold shrink-0">{e.pointer || "(root)"}</span>
+                                        <span>{e.message}</span>
+                                    </li>
+                                ))}
+                            </ul>
+                        )}
                     </div>
                 </div>
 
                 {/* Footer */}
                 <div className="px-6 py-4 border-t border-slate-100 flex items-center justify-between bg-slate-50/30">
                     <p className="text-[10px] text-slate-400">
-                        提示: 编辑后请务必点击保存以生效
+                        提示: 编辑后请务必点击保存以生效{hasSchema && "；标记为 secret 的字段会加密保存到本地密钥库"}
                     </p>
                     <div className="flex items-center gap-3">
                         <button
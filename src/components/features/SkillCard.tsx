@@ -1,10 +1,15 @@
-import { RefreshCw, Box, Trash2, Settings2, Sparkles, History, Github } from "lucide-react";
+import { RefreshCw, Box, Trash2, Settings2, Sparkles, History, Github, Fingerprint } from "lucide-react";
 import { useState } from "react";
-import { cn } from "../../lib/utils";
+import { cn, errorMessage } from "../../lib/utils";
 import { ConfigModal } from "./ConfigModal";
 import { useAppStore } from "../../store/app-store";
 import { invoke } from "@tauri-apps/api/core";
 
+export type SkillScope =
+    | { kind: "global" }
+    | { kind: "user" }
+    | { kind: "project"; path: string };
+
 // Simplified Skill interface for group instances
 export interface Skill {
     id: string;
@@ -17,12 +22,17 @@ export interface Skill {
     version?: string;
     downloads?: number;
     agent?: string;
+    scope?: SkillScope;
+    path?: string;
     is_symlink?: boolean;
     source?: string | null;
     has_update?: boolean;
     local_hash?: string | null;
     remote_hash?: string | null;
     last_updated?: string | null;
+    license?: string | null;
+    allowed_tools?: string[];
+    manifest_error?: string | null;
 }
 
 export interface SkillGroup {
@@ -43,6 +53,11 @@ interface SkillCardProps {
     isSelected?: boolean;
 }
 
+// Project root of a project-scoped skill, passed as the `project` target to commands
+export function projectOf(skill?: Skill): string | null {
+    return skill?.scope?.kind === "project" ? skill.scope.path : null;
+}
+
 export function SkillCard({ skillGroup, onInstall, onUninstall, onRefresh, isInstalling, onSelect, isSelected }: SkillCardProps) {
     const [isConfigOpen, setIsConfigOpen] = useState(false);
     const [isUpdating, setIsUpdating] = useState(false);
@@ -62,7 +77,8 @@ export function SkillCard({ skillGroup, onInstall, onUninstall, onRefresh, isIns
         try {
             await invoke("update_skill_repo", {
                 id: skillGroup.id,
-                agent: skillGroup.instances[0]?.agent || "global"
+                agent: skillGroup.instances[0]?.agent || "global",
+                project: projectOf(skillGroup.instances[0])
             });
             // Success! Clear update state for this skill
             const newUpdates = { ...skillUpdates };
@@ -78,12 +94,31 @@ export function SkillCard({ skillGroup, onInstall, onUninstall, onRefresh, isIns
             onRefresh();
             alert("更新成功！");
         } catch (err) {
-            alert("更新失败: " + err);
+            alert("更新失败: " + errorMessage(err));
         } finally {
             setIsUpdating(false);
         }
     };
 
+    const handleConvert = async (instance: Skill) => {
+        const mode = instance.is_symlink ? "copy" : "symlink";
+        const action = mode === "copy" ? "转换为独立副本" : "链接到全局存储";
+        if (!confirm(`确定要将 ${instance.agent} 的 "${skillGroup.id}" ${action}吗?`)) {
+            return;
+        }
+        try {
+            await invoke("convert_install_mode", {
+                skill: skillGroup.id,
+                agent: instance.agent,
+                mode,
+                project: projectOf(instance)
+            });
+            onRefresh();
+        } catch (err) {
+            alert("转换失败: " + errorMessage(err));
+        }
+    };
+
     // Sort instances: Symlinks first, then alphabetically by agent
     const sortedInstances = [...skillGroup.instances].sort((a, b) => {
         if (a.is_symlink && !b.is_symlink) return -1;
@@ -137,8 +172,20 @@ export function SkillCard({ skillGroup, onInstall, onUninstall, onRefresh, isIns
                     </div>
                     {skillGroup.instances[0]?.local_hash && (
                         <div className="flex items-center gap-1 text-[10px] text-slate-400 font-mono">
-                            <Github size={11} />
-                            <span>{skillGroup.instances[0]?.local_hash}</span>
+                            {skillGroup.instances[0].local_hash.startsWith("sha256:") ? (
+                                // Content digest taken at install time, not a commit
+                                <>
+                                    <Fingerprint size={11} />
+                                    <span title={skillGroup.instances[0].local_hash}>
+                                        {skillGroup.instances[0].local_hash.slice(7, 14)}
+                                    </span>
+                                </>
+                            ) : (
+                                <>
+                                    <Github size={11} />
+                                    <span>{skillGroup.instances[0]?.local_hash}</span>
+                                </>
+                            )}
                         </div>
                     )}
                 </div>
@@ -155,19 +202,25 @@ export function SkillCard({ skillGroup, onInstall, onUninstall, onRefresh, isIns
             {/* Agents Column (Merged) */}
             <div className="flex-1 shrink-0 pr-4 flex flex-wrap gap-1.5">
                 {sortedInstances.map((instance, idx) => (
-                    <div
+                    <button
                         key={`${instance.agent}-${idx}`}
+                        onClick={() => handleConvert(instance)}
+                        disabled={instance.agent === "global"}
                         className={cn(
-                            "flex items-center gap-1 px-2 py-1 rounded-md border text-[10px] font-bold uppercase transition-colors",
+                            "flex items-center gap-1 px-2 py-1 rounded-md border text-[10px] font-bold uppercase transition-colors enabled:hover:border-blue-300",
                             instance.is_symlink
                                 ? "bg-amber-50 text-amber-700 border-amber-200"
                                 : "bg-slate-50 text-slate-600 border-slate-200"
                         )}
-                        title={instance.is_symlink ? "Symlinked" : "Copied"}
+                        title={
+                            instance.agent === "global"
+                                ? "Global store copy"
+                                : instance.is_symlink ? "Symlinked · click to make a copy" : "Copied · click to link to the global store"
+                        }
                     >
                         {instance.agent}
                         {instance.is_symlink && <span className="opacity-50 ml-0.5">🔗</span>}
-                    </div>
+                    </button>
                 ))}
             </div>
 
@@ -213,6 +266,7 @@ export function SkillCard({ skillGroup, onInstall, onUninstall, onRefresh, isIns
                         onClose={() => setIsConfigOpen(false)}
                         skillId={skillGroup.id}
                         agent={skillGroup.instances[0]?.agent || "global"}
+                        project={projectOf(skillGroup.instances[0])}
                     />
                 )}
             </div>
@@ -3,6 +3,15 @@ export interface AgentOption {
     id: string;
 }
 
+// Agent as returned by the backend `list_agents` command
+export interface AgentDefinition {
+    id: string;
+    name: string;
+    path: string;
+    enabled: boolean;
+    builtin: boolean;
+}
+
 export const SUPPORTED_AGENTS: AgentOption[] = [
     { name: "Antigravity", id: "antigravity" },
     { name: "Claude Code", id: "claude-code" },
@@ -4,3 +4,49 @@ import { twMerge } from "tailwind-merge"
 export function cn(...inputs: ClassValue[]) {
     return twMerge(clsx(inputs))
 }
+
+/** Error returned by backend commands. */
+export interface SkillsError {
+    kind: string
+    message: string
+    context: {
+        path: string | null
+        agent: string | null
+        skill: string | null
+        /** Per-field problems, keyed by JSON pointer ("" for the whole value) */
+        errors?: { pointer: string, message: string }[]
+        /** Links that still point at the path ("has_dependents") */
+        dependents?: string[]
+    }
+}
+
+export function isSkillsError(err: unknown): err is SkillsError {
+    return typeof err === "object" && err !== null && "kind" in err && "message" in err
+}
+
+/** Message for an invoke() failure, with a hint for errors the user can fix. */
+export function errorMessage(err: unknown): string {
+    if (!isSkillsError(err)) {
+        return String(err)
+    }
+    switch (err.kind) {
+        case "git":
+            return `${err.message}. Resolve it in the repository at ${err.context.path ?? "this path"}, then retry.`
+        case "command_not_found":
+            return `${err.message}. Install Node.js (for npx) or switch to the native installer.`
+        case "permission_denied":
+            return `${err.message}. Check that you can write to ${err.context.path ?? "this directory"}.`
+        case "unknown_agent":
+            return `${err.message}. Enable or add the agent in Settings.`
+        case "has_dependents":
+            return `${err.message}. Remove those links first, or remove them together with the copy.`
+        case "invalid_skill_config":
+            return `${err.message}. Fix the listed fields and save again.`
+        case "vault_locked":
+            return `${err.message}. Enter the vault passphrase to store secret config values.`
+        case "integrity_mismatch":
+            return `${err.message}. The source changed since skills.lock was written; turn off Frozen Install to update it.`
+        default:
+            return err.message
+    }
+}
@@ -1,11 +1,15 @@
 import { RotateCw, Download, Trash2, Sparkles } from "lucide-react";
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useAppStore } from "../store/app-store";
-import { cn } from "../lib/utils";
+import { cn, errorMessage, isSkillsError } from "../lib/utils";
 import { SkillCard, type Skill } from "../components/features/SkillCard";
 
-
+/** Payload of `skills://changed`: skills added, edited or removed on disk. */
+interface SkillsChanged {
+    skills: { id: string; agent: string; path: string; skill: Skill | null }[];
+}
 
 export default function Discover() {
     // eslint-disable-next-line @typescript-eslint/no-unused-vars
@@ -53,7 +57,7 @@ export default function Discover() {
             .catch((err) => {
                 console.error(err);
                 if (explicit) {
-                    setUpdateFeedback("检查更新失败: " + err);
+                    setUpdateFeedback("检查更新失败: " + errorMessage(err));
                     setTimeout(() => setUpdateFeedback(null), 5000);
                 }
             })
@@ -64,6 +68,21 @@ export default function Discover() {
         loadSkills();
     }, []);
 
+    // Apply changes made outside the app without reloading everything
+    useEffect(() => {
+        const unlisten = listen<SkillsChanged>("skills://changed", (event) => {
+            const changed = event.payload.skills;
+            const paths = new Set(changed.map((c) => c.path));
+            setSkills((current) => [
+                ...current.filter((s) => !s.path || !paths.has(s.path)),
+                ...changed.flatMap((c) => (c.skill ? [c.skill] : [])),
+            ]);
+        });
+        return () => {
+            unlisten.then((stop) => stop());
+        };
+    }, []);
+
     // Smart Filtering: Get unique agents from fetched skills
     const availableAgents = Array.from(new Set(skills.map(s => s.agent || "Other"))).sort();
 
@@ -112,7 +131,8 @@ export default function Discover() {
             global: installConfig.installGlobal,
             agents: installConfig.targetAgents,
             autoConfirm: installConfig.autoConfirm,
-            installMode: installConfig.installMode
+            installMode: installConfig.installMode,
+            frozen: installConfig.frozen
         })
             .then(() => {
                 alert("安装成功！");
@@ -123,11 +143,20 @@ export default function Discover() {
             })
             .catch((err) => {
                 console.error(err);
-                alert("安装失败: " + err);
+                alert("安装失败: " + errorMessage(err));
                 setInstalling(null);
             });
     };
 
+    // Removing a copy that other agents still link to fails; ask before
+    // taking those links with it
+    const removeWithCascade = (args: Record<string, unknown>) =>
+        invoke("remove_skills", args).catch(err => {
+            if (!isSkillsError(err) || err.kind !== "has_dependents") throw err;
+            if (!confirm(`${err.message}\n\n是否同时删除这些链接?`)) return;
+            return invoke("remove_skills", { ...args, cascade: true });
+        });
+
     const handleUninstall = (id: string, skillAgents: string[]) => {
         // Determine targets based on view
         let targetAgents: string[] = [];
@@ -156,7 +185,7 @@ export default function Discover() {
             return;
         }
 
-        invoke("remove_skills", {
+        removeWithCascade({
             skillIds: [id],
             global: isGlobal,
             agents: targetAgents,
@@ -170,29 +199,19 @@ export default function Discover() {
             })
             .catch(err => {
                 console.error(err);
-                alert("卸载失败: " + err);
+                alert("卸载失败: " + errorMessage(err));
             });
     };
 
-    const handleExport = () => {
-        // Simple export of all unique skills with their sources
-        // Group by ID
-        const aggregated = skills.reduce((acc, skill) => {
-            if (!acc[skill.id]) {
-                acc[skill.id] = { id: skill.id, agents: new Set<string>(), source: skill.source };
-            }
-            if (skill.agent) acc[skill.id].agents.add(skill.agent);
-            return acc;
-        }, {} as Record<string, { id: string, agents: Set<string>, source?: string | null }>);
-
-        const data = {
-            exportedAt: new Date().toISOString(),
-            skills: Object.values(aggregated).map(s => ({
-                id: s.id,
-                agents: Array.from(s.agents),
-                source: s.source
-            }))
-        };
+    const handleExport = async () => {
+        // Skills with their sources and configs; secret config values come back redacted
+        let data;
+        try {
+            data = await invoke("export_skills");
+        } catch (err) {
+            alert("Export failed: " + errorMessage(err));
+            return;
+        }
 
         const blob = new Blob([JSON.stringify(data, null, 2)], { type: "application/json" });
         const url = URL.createObjectURL(blob);
@@ -241,7 +260,7 @@ export default function Discover() {
         const isGlobal = selectedAgent === "global";
         const targetAgents = selectedAgent === "All" ? [] : [selectedAgent].filter(a => a !== "global");
 
-        invoke("remove_skills", {
+        removeWithCascade({
             skillIds: skillList,
             global: isGlobal,
             agents: targetAgents,
@@ -254,7 +273,7 @@ export default function Discover() {
             })
             .catch(err => {
                 console.error(err);
-                alert("批量删除失败: " + err);
+                alert("批量删除失败: " + errorMessage(err));
             })
             .finally(() => setLoading(false));
     };
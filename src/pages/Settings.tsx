@@ -1,9 +1,88 @@
-import { useState } from "react";
+import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { SUPPORTED_AGENTS } from "../data/supported-agents";
+import { listen } from "@tauri-apps/api/event";
+import { SUPPORTED_AGENTS, type AgentOption, type AgentDefinition } from "../data/supported-agents";
 import { useAppStore } from "../store/app-store";
-import { cn } from "../lib/utils";
-import { type Skill } from "../components/features/SkillCard";
+import { cn, errorMessage, type SkillsError } from "../lib/utils";
+
+interface ProgressEvent {
+    operation_id: string;
+    phase: string;
+    percent: number;
+    line: string | null;
+}
+
+interface ImportResult {
+    source: string;
+    skill: string | null;
+    error: SkillsError | null;
+}
+
+interface SyncStep {
+    source: string;
+    reference: string | null;
+    skill: string | null;
+    agents: string[];
+    global: boolean;
+    mode: string;
+    reason: string;
+}
+
+interface SyncPlan {
+    install: SyncStep[];
+    upgrade: SyncStep[];
+    remove: { skill: string; agent: string; path: string }[];
+    unchanged: number;
+}
+
+interface SkillVerification {
+    id: string;
+    agent: string;
+    path: string;
+    status: "intact" | "modified" | "unknown";
+    modified: string[];
+    added: string[];
+    deleted: string[];
+}
+
+interface DuplicateCopy {
+    agent: string;
+    path: string;
+    reference: boolean;
+    modified: string[];
+    added: string[];
+    deleted: string[];
+}
+
+interface DuplicateGroup {
+    name: string;
+    source: string | null;
+    project: string | null;
+    status: "identical" | "drifted";
+    copies: DuplicateCopy[];
+}
+
+interface TrashEntry {
+    id: string;
+    name: string;
+    agent: string;
+    origin: string;
+    deleted_at: number;
+    link_target: string | null;
+}
+
+interface BrokenLink {
+    agent: string;
+    path: string;
+    target: string;
+    problem: "dangling" | "outside_store" | "loop";
+    store_copy: string | null;
+}
+
+interface SyncReport {
+    installed: ImportResult[];
+    removed: { skill: string; agent: string; error: SkillsError | null }[];
+}
 
 export default function Settings() {
     const { installConfig, setInstallConfig } = useAppStore();
@@ -11,42 +90,46 @@ export default function Settings() {
     const [importText, setImportText] = useState("");
     const [importing, setImporting] = useState(false);
     const [importProgress, setImportProgress] = useState("");
+    const [importOperation, setImportOperation] = useState<string | null>(null);
     const [copyFeedback, setCopyFeedback] = useState(false);
+    const [agentOptions, setAgentOptions] = useState<AgentOption[]>(SUPPORTED_AGENTS);
+    const [manifestText, setManifestText] = useState("");
+    const [manifestFeedback, setManifestFeedback] = useState(false);
+    const [syncPlan, setSyncPlan] = useState<SyncPlan | null>(null);
+    const [syncing, setSyncing] = useState(false);
+    const [syncProgress, setSyncProgress] = useState("");
+    const [syncOperation, setSyncOperation] = useState<string | null>(null);
+    const [verifying, setVerifying] = useState(false);
+    const [verifyResults, setVerifyResults] = useState<SkillVerification[] | null>(null);
+    const [findingDuplicates, setFindingDuplicates] = useState(false);
+    const [duplicates, setDuplicates] = useState<DuplicateGroup[] | null>(null);
+    const [trash, setTrash] = useState<TrashEntry[]>([]);
+    const [trashRetention, setTrashRetention] = useState(30);
+    const [checkingLinks, setCheckingLinks] = useState(false);
+    const [brokenLinks, setBrokenLinks] = useState<BrokenLink[] | null>(null);
+
+    useEffect(() => {
+        // Prefer the backend registry so user-added agents show up too
+        invoke<AgentDefinition[]>("list_agents")
+            .then((agents) => setAgentOptions(
+                agents
+                    .filter(a => a.enabled && a.id !== "global")
+                    .map(a => ({ name: a.name, id: a.id }))
+            ))
+            .catch(console.error);
+    }, []);
 
     const handleExport = async () => {
         try {
-            const skills = await invoke<Skill[]>("get_local_skills");
-
-            // Aggregate skills by ID
-            const aggregated = skills.reduce((acc, skill) => {
-                if (!acc[skill.id]) {
-                    acc[skill.id] = {
-                        id: skill.id,
-                        agents: new Set<string>(),
-                        source: skill.source // Keep the skill source URL
-                    };
-                }
-                if (skill.agent) {
-                    acc[skill.id].agents.add(skill.agent);
-                }
-                return acc;
-            }, {} as Record<string, { id: string, agents: Set<string>, source?: string | null }>);
-
-            const exportData = {
-                exportedAt: new Date().toISOString(),
-                skills: Object.values(aggregated).map(s => ({
-                    id: s.id,
-                    agents: Array.from(s.agents),
-                    source: s.source
-                }))
-            };
+            // Secret config values come back redacted
+            const exportData = await invoke("export_skills");
 
             await navigator.clipboard.writeText(JSON.stringify(exportData, null, 2));
             setCopyFeedback(true);
             setTimeout(() => setCopyFeedback(false), 2000);
         } catch (error) {
             console.error("Export failed:", error);
-            alert("Export failed: " + error);
+            alert("Export failed: " + errorMessage(error));
         }
     };
 
@@ -61,12 +144,8 @@ export default function Settings() {
 
             setImporting(true);
             const total = data.skills.length;
-            let current = 0;
-
-            for (const skill of data.skills) {
-                current++;
-                setImportProgress(`Installing ${current}/${total}: ${skill.id}`);
 
+            const entries = data.skills.map((skill: any) => {
                 const rawAgents = skill.agents || installConfig.targetAgents;
                 // Filter out 'global' from --agent flags as it's handled by --global flag
                 const agents = rawAgents.filter((a: string) => a !== 'global');
@@ -91,18 +170,29 @@ export default function Settings() {
                     }
                 }
 
-                try {
-                    await invoke("install_skill", {
-                        id: installId,
-                        skill: skillName,
-                        global: isGlobal,
-                        agents: agents,
-                        autoConfirm: true,
-                        installMode: installConfig.installMode
-                    });
-                } catch (err) {
-                    console.error(`Failed to install ${skill.id}:`, err);
-                }
+                return { source: installId, skill: skillName, agents, global: isGlobal };
+            });
+
+            const operationId = `import-${Date.now()}`;
+            setImportOperation(operationId);
+            const unlisten = await listen<ProgressEvent>("operation://progress", (event) => {
+                const progress = event.payload;
+                if (progress.operation_id !== operationId) return;
+                setImportProgress(`[${progress.percent}%] ${progress.line ?? progress.phase}`);
+            });
+
+            try {
+                const results = await invoke<ImportResult[]>("import_skills", {
+                    entries,
+                    installMode: installConfig.installMode,
+                    operationId
+                });
+                const failed = results.filter((r) => r.error);
+                failed.forEach((r) => console.error(`Failed to install ${r.source}:`, errorMessage(r.error)));
+                setImportProgress(`Installed ${total - failed.length}/${total}`);
+            } finally {
+                unlisten();
+                setImportOperation(null);
             }
 
             setImportProgress("Batch installation complete!");
@@ -114,11 +204,176 @@ export default function Settings() {
 
         } catch (error) {
             console.error("Import failed:", error);
-            alert("Import failed: " + error);
+            alert("Import failed: " + errorMessage(error));
             setImporting(false);
         }
     };
 
+    const handleExportManifest = async () => {
+        try {
+            const manifest = await invoke<string>("export_manifest");
+            await navigator.clipboard.writeText(manifest);
+            setManifestFeedback(true);
+            setTimeout(() => setManifestFeedback(false), 2000);
+        } catch (error) {
+            console.error("Manifest export failed:", error);
+            alert("Export failed: " + errorMessage(error));
+        }
+    };
+
+    const handlePlanSync = async () => {
+        if (!manifestText.trim()) return;
+        try {
+            setSyncPlan(await invoke<SyncPlan>("plan_sync", { manifest: manifestText }));
+        } catch (error) {
+            console.error("Sync plan failed:", error);
+            alert("Invalid manifest: " + errorMessage(error));
+        }
+    };
+
+    const handleApplySync = async () => {
+        const operationId = `sync-${Date.now()}`;
+        setSyncing(true);
+        setSyncOperation(operationId);
+        const unlisten = await listen<ProgressEvent>("operation://progress", (event) => {
+            const progress = event.payload;
+            if (progress.operation_id !== operationId) return;
+            setSyncProgress(`[${progress.percent}%] ${progress.line ?? progress.phase}`);
+        });
+
+        try {
+            const report = await invoke<SyncReport>("apply_sync", {
+                manifest: manifestText,
+                frozen: installConfig.frozen,
+                operationId
+            });
+            const failed = [...report.installed, ...report.removed].filter((r) => r.error);
+            failed.forEach((r) => console.error("Sync step failed:", errorMessage(r.error)));
+            setSyncProgress(failed.length ? `Done with ${failed.length} failures` : "In sync!");
+            setSyncPlan(null);
+        } catch (error) {
+            console.error("Sync failed:", error);
+            alert("Sync failed: " + errorMessage(error));
+        } finally {
+            unlisten();
+            setSyncOperation(null);
+            setTimeout(() => {
+                setSyncing(false);
+                setSyncProgress("");
+            }, 2000);
+        }
+    };
+
+    const handleVerify = async () => {
+        setVerifying(true);
+        try {
+            const results = await invoke<SkillVerification[]>("verify_skills");
+            setVerifyResults(results.filter((r) => r.status === "modified"));
+        } catch (error) {
+            console.error("Verify failed:", error);
+            alert("Verify failed: " + errorMessage(error));
+        } finally {
+            setVerifying(false);
+        }
+    };
+
+    const handleFindDuplicates = async () => {
+        setFindingDuplicates(true);
+        try {
+            setDuplicates(await invoke<DuplicateGroup[]>("find_duplicates"));
+        } catch (error) {
+            console.error("Duplicate search failed:", error);
+            alert("Duplicate search failed: " + errorMessage(error));
+        } finally {
+            setFindingDuplicates(false);
+        }
+    };
+
+    // Identical copies become links to one copy in the global store
+    const handleLinkDuplicates = async (group: DuplicateGroup) => {
+        try {
+            for (const copy of group.copies.filter((c) => c.agent !== "global")) {
+                await invoke("convert_install_mode", {
+                    skill: group.name,
+                    agent: copy.agent,
+                    mode: "symlink",
+                    project: group.project,
+                });
+            }
+        } catch (error) {
+            console.error("Linking failed:", error);
+            alert("Linking failed: " + errorMessage(error));
+        }
+        handleFindDuplicates();
+        loadTrash();
+    };
+
+    const loadTrash = () => {
+        invoke<TrashEntry[]>("list_trash").then(setTrash).catch(console.error);
+    };
+
+    useEffect(() => {
+        loadTrash();
+        invoke<number>("get_trash_retention").then(setTrashRetention).catch(console.error);
+    }, []);
+
+    const handleRestore = async (id: string) => {
+        try {
+            await invoke("restore_from_trash", { id });
+        } catch (error) {
+            console.error("Restore failed:", error);
+            alert("Restore failed: " + errorMessage(error));
+        }
+        loadTrash();
+    };
+
+    const handlePurge = async (ids?: string[]) => {
+        if (!ids && !confirm("Permanently delete everything in the trash?")) return;
+        try {
+            await invoke("purge_trash", { ids: ids ?? null });
+        } catch (error) {
+            console.error("Purge failed:", error);
+            alert("Purge failed: " + errorMessage(error));
+        }
+        loadTrash();
+    };
+
+    const handleRetention = async (days: number) => {
+        setTrashRetention(days);
+        try {
+            await invoke("set_trash_retention", { days });
+        } catch (error) {
+            console.error("Failed to save retention:", error);
+        }
+    };
+
+    const handleCheckLinks = async () => {
+        setCheckingLinks(true);
+        try {
+            const report = await invoke<{ broken_links: BrokenLink[] }>("run_doctor");
+            setBrokenLinks(report.broken_links);
+        } catch (error) {
+            console.error("Link check failed:", error);
+            alert("Link check failed: " + errorMessage(error));
+        } finally {
+            setCheckingLinks(false);
+        }
+    };
+
+    const handleRepairLink = async (path: string, fix: "relink" | "copy" | "delete") => {
+        try {
+            const repaired = await invoke<{ skipped?: string[] }>("repair_link", { path, fix });
+            if (repaired.skipped?.length) {
+                alert("These links point outside the skill and were not copied:\n" + repaired.skipped.join("\n"));
+            }
+            setBrokenLinks((links) => links?.filter((l) => l.path !== path) ?? null);
+            if (fix === "delete") loadTrash();
+        } catch (error) {
+            console.error("Repair failed:", error);
+            alert("Repair failed: " + errorMessage(error));
+        }
+    };
+
     const Toggle = ({ title, desc, active, onToggle }: { title: string, desc: string, active: boolean, onToggle: () => void }) => (
         <div className="flex items-center justify-between py-4 border-b border-slate-100 last:border-0">
             <div>
@@ -190,6 +445,12 @@ export default function Settings() {
                                 active={installConfig.installGlobal}
                                 onToggle={() => setInstallConfig({ installGlobal: !installConfig.installGlobal })}
                             />
+                            <Toggle
+                                title="Frozen Install"
+                                desc="Install the exact commits in skills.lock and refuse anything that changed"
+                                active={installConfig.frozen}
+                                onToggle={() => setInstallConfig({ frozen: !installConfig.frozen })}
+                            />
                             <Toggle
                                 title="Auto Confirm"
                                 desc="Skip all confirmation prompts during installation (-y)"
@@ -203,7 +464,7 @@ export default function Settings() {
                                 </div>
 
                                 <div className="bg-white border border-slate-200 rounded-lg p-2 max-h-60 overflow-y-auto grid grid-cols-2 gap-2 no-scrollbar">
-                                    {SUPPORTED_AGENTS.map((agent) => {
+                                    {agentOptions.map((agent) => {
                                         const isSelected = installConfig.targetAgents.includes(agent.id);
                                         return (
                                             <button
@@ -267,6 +528,190 @@ export default function Settings() {
                                 </div>
                             </div>
 
+                            <div className="border-t border-slate-200/50 pt-6">
+                                <div className="flex items-center justify-between">
+                                    <div>
+                                        <h3 className="text-sm font-bold text-slate-800">Verify Installed Skills</h3>
+                                        <p className="text-xs text-slate-400">Find skills whose files changed since they were installed</p>
+                                    </div>
+                                    <button
+                                        onClick={handleVerify}
+                                        disabled={verifying}
+                                        className="px-4 py-2 rounded-lg text-xs font-bold transition-all shadow-sm border bg-white border-slate-200 text-slate-600 hover:text-blue-600 hover:border-blue-200 disabled:opacity-50"
+                                    >
+                                        {verifying ? "Checking..." : "Verify"}
+                                    </button>
+                                </div>
+                                {verifyResults && (
+                                    <div className="mt-3 bg-white border border-slate-200 rounded-lg p-3 text-xs text-slate-600 space-y-1">
+                                        {verifyResults.length === 0 && (
+                                            <p className="font-bold text-green-600">All tracked skills are unchanged</p>
+                                        )}
+                                        {verifyResults.map((r) => (
+                                            <p key={r.path} title={r.path}>
+                                                <span className="font-bold text-amber-600">{r.id}</span> ({r.agent}){" "}
+                                                {[...r.modified.map(f => `~${f}`), ...r.added.map(f => `+${f}`), ...r.deleted.map(f => `-${f}`)].join(" ")}
+                                            </p>
+                                        ))}
+                                    </div>
+                                )}
+                            </div>
+
+                            <div className="border-t border-slate-200/50 pt-6">
+                                <div className="flex items-center justify-between">
+                                    <div>
+                                        <h3 className="text-sm font-bold text-slate-800">Duplicate Skills</h3>
+                                        <p className="text-xs text-slate-400">Find skills copied into several agents and copies that have drifted apart</p>
+                                    </div>
+                                    <button
+                                        onClick={handleFindDuplicates}
+                                        disabled={findingDuplicates}
+                                        className="px-4 py-2 rounded-lg text-xs font-bold transition-all shadow-sm border bg-white border-slate-200 text-slate-600 hover:text-blue-600 hover:border-blue-200 disabled:opacity-50"
+                                    >
+                                        {findingDuplicates ? "Searching..." : "Find Duplicates"}
+                                    </button>
+                                </div>
+                                {duplicates && (
+                                    <div className="mt-3 bg-white border border-slate-200 rounded-lg p-3 text-xs text-slate-600 space-y-2">
+                                        {duplicates.length === 0 && (
+                                            <p className="font-bold text-green-600">No skill is installed as more than one copy</p>
+                                        )}
+                                        {duplicates.map((g) => (
+                                            <div key={`${g.project ?? ""}/${g.name}/${g.source ?? ""}`}>
+                                                <div className="flex items-center justify-between gap-2">
+                                                    <p className="truncate" title={g.source ?? undefined}>
+                                                        <span className={cn("font-bold", g.status === "drifted" ? "text-amber-600" : "text-slate-800")}>{g.name}</span>{" "}
+                                                        {g.status === "drifted" ? "drifted" : "identical"} in {g.copies.map((c) => c.agent).join(", ")}
+                                                    </p>
+                                                    {g.status === "identical" && (
+                                                        <button
+                                                            onClick={() => handleLinkDuplicates(g)}
+                                                            className="font-bold text-blue-600 hover:text-blue-700 shrink-0"
+                                                        >
+                                                            Link to One Copy
+                                                        </button>
+                                                    )}
+                                                </div>
+                                                {g.status === "drifted" && g.copies.filter((c) => !c.reference).map((c) => (
+                                                    <p key={c.path} className="pl-3 text-slate-400" title={c.path}>
+                                                        {c.agent}:{" "}
+                                                        {[...c.modified.map(f => `~${f}`), ...c.added.map(f => `+${f}`), ...c.deleted.map(f => `-${f}`)].join(" ") || "same as reference"}
+                                                    </p>
+                                                ))}
+                                            </div>
+                                        ))}
+                                    </div>
+                                )}
+                            </div>
+
+                            <div className="border-t border-slate-200/50 pt-6">
+                                <div className="flex items-center justify-between">
+                                    <div>
+                                        <h3 className="text-sm font-bold text-slate-800">Skill Links</h3>
+                                        <p className="text-xs text-slate-400">Find symlinks that are dangling, loop or point outside the global store</p>
+                                    </div>
+                                    <button
+                                        onClick={handleCheckLinks}
+                                        disabled={checkingLinks}
+                                        className="px-4 py-2 rounded-lg text-xs font-bold transition-all shadow-sm border bg-white border-slate-200 text-slate-600 hover:text-blue-600 hover:border-blue-200 disabled:opacity-50"
+                                    >
+                                        {checkingLinks ? "Checking..." : "Check Links"}
+                                    </button>
+                                </div>
+                                {brokenLinks && (
+                                    <div className="mt-3 bg-white border border-slate-200 rounded-lg p-3 text-xs text-slate-600 space-y-2">
+                                        {brokenLinks.length === 0 && (
+                                            <p className="font-bold text-green-600">All skill links are healthy</p>
+                                        )}
+                                        {brokenLinks.map((l) => (
+                                            <div key={l.path} className="flex items-center justify-between gap-2">
+                                                <p className="truncate" title={`${l.path} -> ${l.target}`}>
+                                                    <span className="font-bold text-amber-600">{l.path.split(/[\\/]/).pop()}</span> ({l.agent}){" "}
+                                                    <span className="text-slate-400">{l.problem.replace("_", " ")}</span>
+                                                </p>
+                                                <div className="flex gap-2 shrink-0">
+                                                    <button
+                                                        onClick={() => handleRepairLink(l.path, "relink")}
+                                                        disabled={!l.store_copy}
+                                                        title={l.store_copy ?? "No global copy to link to"}
+                                                        className="font-bold text-blue-600 hover:text-blue-700 disabled:opacity-40"
+                                                    >
+                                                        Relink
+                                                    </button>
+                                                    <button
+                                                        onClick={() => handleRepairLink(l.path, "copy")}
+                                                        disabled={l.problem !== "outside_store" && !l.store_copy}
+                                                        className="font-bold text-blue-600 hover:text-blue-700 disabled:opacity-40"
+                                                    >
+                                                        Make Copy
+                                                    </button>
+                                                    <button
+                                                        onClick={() => handleRepairLink(l.path, "delete")}
+                                                        className="font-bold text-slate-400 hover:text-red-600"
+                                                    >
+                                                        Delete
+                                                    </button>
+                                                </div>
+                                            </div>
+                                        ))}
+                                    </div>
+                                )}
+                            </div>
+
+                            <div className="border-t border-slate-200/50 pt-6">
+                                <div className="flex items-center justify-between">
+                                    <div>
+                                        <h3 className="text-sm font-bold text-slate-800">Trash</h3>
+                                        <p className="text-xs text-slate-400">Removed skills are kept here until restored or purged</p>
+                                    </div>
+                                    <div className="flex items-center gap-2">
+                                        <label className="text-xs text-slate-500">Keep for</label>
+                                        <input
+                                            type="number"
+                                            min={0}
+                                            value={trashRetention}
+                                            onChange={(e) => handleRetention(Math.max(0, Number(e.target.value) || 0))}
+                                            title="0 keeps removed skills forever"
+                                            className="w-16 px-2 py-1.5 bg-white border border-slate-200 rounded-lg text-xs text-slate-600"
+                                        />
+                                        <span className="text-xs text-slate-500">days</span>
+                                        <button
+                                            onClick={() => handlePurge()}
+                                            disabled={trash.length === 0}
+                                            className="px-4 py-2 rounded-lg text-xs font-bold transition-all shadow-sm border bg-white border-slate-200 text-slate-600 hover:text-red-600 hover:border-red-200 disabled:opacity-50"
+                                        >
+                                            Empty Trash
+                                        </button>
+                                    </div>
+                                </div>
+                                {trash.length > 0 && (
+                                    <div className="mt-3 bg-white border border-slate-200 rounded-lg p-3 text-xs text-slate-600 space-y-2">
+                                        {trash.map((t) => (
+                                            <div key={t.id} className="flex items-center justify-between gap-2">
+                                                <p className="truncate" title={t.origin}>
+                                                    <span className="font-bold text-slate-800">{t.name}</span> ({t.agent}){" "}
+                                                    <span className="text-slate-400">{new Date(t.deleted_at * 1000).toLocaleString()}</span>
+                                                </p>
+                                                <div className="flex gap-2 shrink-0">
+                                                    <button
+                                                        onClick={() => handleRestore(t.id)}
+                                                        className="font-bold text-blue-600 hover:text-blue-700"
+                                                    >
+                                                        Restore
+                                                    </button>
+                                                    <button
+                                                        onClick={() => handlePurge([t.id])}
+                                                        className="font-bold text-slate-400 hover:text-red-600"
+                                                    >
+                                                        Delete
+                                                    </button>
+                                                </div>
+                                            </div>
+                                        ))}
+                                    </div>
+                                )}
+                            </div>
+
                             <div className="border-t border-slate-200/50 pt-6">
                                 <div className="mb-3">
                                     <h3 className="text-sm font-bold text-slate-800">Batch Install / Import</h3>
@@ -284,6 +729,14 @@ export default function Settings() {
                                             {importProgress}
                                         </span>
                                     )}
+                                    {importOperation && (
+                                        <button
+                                            onClick={() => invoke("cancel_operation", { id: importOperation }).catch(console.error)}
+                                            className="h-9 px-4 bg-slate-100 hover:bg-slate-200 text-slate-600 text-xs font-bold rounded-lg transition-all"
+                                        >
+                                            Cancel
+                                        </button>
+                                    )}
                                     <button
                                         onClick={handleImport}
                                         disabled={importing || !importText.trim()}
@@ -293,6 +746,80 @@ export default function Settings() {
                                     </button>
                                 </div>
                             </div>
+
+                            <div className="border-t border-slate-200/50 pt-6">
+                                <div className="flex items-center justify-between mb-3">
+                                    <div>
+                                        <h3 className="text-sm font-bold text-slate-800">Team Manifest (skills.toml)</h3>
+                                        <p className="text-xs text-slate-400">Install, upgrade and remove skills to match a shared manifest</p>
+                                    </div>
+                                    <button
+                                        onClick={handleExportManifest}
+                                        className={cn(
+                                            "px-4 py-2 rounded-lg text-xs font-bold transition-all shadow-sm border",
+                                            manifestFeedback
+                                                ? "bg-green-50 border-green-200 text-green-700"
+                                                : "bg-white border-slate-200 text-slate-600 hover:text-blue-600 hover:border-blue-200"
+                                        )}
+                                    >
+                                        {manifestFeedback ? "Copied!" : "Copy skills.toml"}
+                                    </button>
+                                </div>
+                                <textarea
+                                    value={manifestText}
+                                    onChange={(e) => {
+                                        setManifestText(e.target.value);
+                                        setSyncPlan(null);
+                                    }}
+                                    placeholder={'Paste skills.toml here...\n[[sources]]\nsource = "owner/repo"'}
+                                    className="w-full h-32 p-3 bg-white border border-slate-200 rounded-lg text-xs font-mono text-slate-600 focus:outline-none focus:border-blue-500 focus:ring-1 focus:ring-blue-500 transition-all resize-none mb-3"
+                                />
+                                {syncPlan && (
+                                    <div className="mb-3 bg-white border border-slate-200 rounded-lg p-3 text-xs text-slate-600 space-y-1">
+                                        {syncPlan.install.length + syncPlan.upgrade.length + syncPlan.remove.length === 0 && (
+                                            <p className="font-bold text-green-600">Already in sync ({syncPlan.unchanged} skills)</p>
+                                        )}
+                                        {syncPlan.install.map((s, i) => (
+                                            <p key={`i${i}`}><span className="font-bold text-blue-600">+ install</span> {s.skill ?? s.source} → {[...(s.global ? ["global"] : []), ...s.agents].join(", ")}</p>
+                                        ))}
+                                        {syncPlan.upgrade.map((s, i) => (
+                                            <p key={`u${i}`}><span className="font-bold text-amber-600">~ upgrade</span> {s.skill ?? s.source} ({s.reason})</p>
+                                        ))}
+                                        {syncPlan.remove.map((r, i) => (
+                                            <p key={`r${i}`}><span className="font-bold text-red-600">- remove</span> {r.skill} from {r.agent}</p>
+                                        ))}
+                                    </div>
+                                )}
+                                <div className="flex items-center justify-end gap-3">
+                                    {syncing && (
+                                        <span className="text-xs font-bold text-blue-600 animate-pulse">
+                                            {syncProgress}
+                                        </span>
+                                    )}
+                                    {syncOperation && (
+                                        <button
+                                            onClick={() => invoke("cancel_operation", { id: syncOperation }).catch(console.error)}
+                                            className="h-9 px-4 bg-slate-100 hover:bg-slate-200 text-slate-600 text-xs font-bold rounded-lg transition-all"
+                                        >
+                                            Cancel
+                                        </button>
+                                    )}
+                                    <button
+                                        onClick={handlePlanSync}
+                                        disabled={syncing || !manifestText.trim()}
+                                        className="h-9 px-4 bg-white border border-slate-200 hover:border-blue-200 text-slate-600 text-xs font-bold rounded-lg transition-all disabled:opacity-50 disabled:cursor-not-allowed"
+                                    >
+                                        Preview Changes
+                                    </button>
+                                    <button
+                                        onClick={handleApplySync}
+                                        disabled={syncing || !syncPlan}
+                                        className="h-9 px-4 bg-blue-600 hover:bg-blue-700 text-white text-xs font-bold rounded-lg transition-all shadow-sm shadow-blue-200 disabled:opacity-50 disabled:cursor-not-allowed"
+                                    >
+                                        Apply Sync
+                                    </button>
+                                </div>
+                            </div>
                         </div>
                     </div>
                 </div>
@@ -7,6 +7,7 @@ interface InstallConfig {
     targetAgents: string[]
     autoConfirm: boolean
     installMode: 'symlink' | 'copy' // New config
+    frozen: boolean // Only install what skills.lock records
 }
 
 interface AppState {
@@ -27,7 +28,8 @@ export const useAppStore = create<AppState>((set) => ({
         installGlobal: true,
         targetAgents: ["antigravity"],
         autoConfirm: true,
-        installMode: 'symlink' // Default
+        installMode: 'symlink', // Default
+        frozen: false
     },
     setInstallConfig: (config) => set((state) => ({
         installConfig: { ...state.installConfig, ...config }